@@ -1,5 +1,6 @@
 use std::env;
-use std::fs::File;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
 use std::path::Path;
 use std::process::Command;
 
@@ -15,6 +16,8 @@ fn main() {
         .write_bindings(GlobalGenerator, &mut file)
         .unwrap();
 
+    generate_unicode_names(Path::new(&dest));
+
     #[cfg(windows)]
     embed_resource::compile("../extra/windows/windows.rc");
 }
@@ -27,3 +30,19 @@ fn commit_hash() -> String {
         .and_then(|output| String::from_utf8(output.stdout).ok())
         .unwrap_or_default()
 }
+
+/// Generate the table of named characters used by the character picker.
+fn generate_unicode_names(dest: &Path) {
+    let names = fs::read_to_string("res/unicode_names.txt").unwrap();
+    let mut file = BufWriter::new(File::create(dest.join("unicode_names.rs")).unwrap());
+
+    writeln!(file, "pub static UNICODE_NAMES: &[(char, &str, &str)] = &[").unwrap();
+    for line in names.lines().filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        let mut fields = line.split(';');
+        let code = u32::from_str_radix(fields.next().unwrap(), 16).unwrap();
+        let name = fields.next().unwrap().to_lowercase();
+        let keywords = fields.next().unwrap_or_default();
+        writeln!(file, "    ('\\u{{{:x}}}', {:?}, {:?}),", code, name, keywords).unwrap();
+    }
+    writeln!(file, "];").unwrap();
+}
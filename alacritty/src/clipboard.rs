@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
 use std::ffi::c_void;
 
@@ -15,9 +16,19 @@ use copypasta::x11_clipboard::{Primary as X11SelectionClipboard, X11ClipboardCon
 use copypasta::ClipboardContext;
 use copypasta::ClipboardProvider;
 
+#[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+use crate::selection_owner::SelectionOwner;
+
 pub struct Clipboard {
     clipboard: Box<dyn ClipboardProvider>,
     selection: Option<Box<dyn ClipboardProvider>>,
+
+    /// X11 selection owner used for content with custom MIME types.
+    #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+    x11: Option<SelectionOwner>,
+
+    /// Text previously stored in the clipboard.
+    history: ClipboardHistory,
 }
 
 impl Clipboard {
@@ -32,7 +43,13 @@ impl Clipboard {
             Some(display) => {
                 let (selection, clipboard) =
                     wayland_clipboard::create_clipboards_from_external(display);
-                Self { clipboard: Box::new(clipboard), selection: Some(Box::new(selection)) }
+                Self {
+                    clipboard: Box::new(clipboard),
+                    selection: Some(Box::new(selection)),
+                    #[cfg(feature = "x11")]
+                    x11: None,
+                    history: Default::default(),
+                }
             },
             None => Self::default(),
         }
@@ -42,19 +59,31 @@ impl Clipboard {
     /// feature.
     #[cfg(any(test, not(any(feature = "x11", target_os = "macos", windows))))]
     pub fn new_nop() -> Self {
-        Self { clipboard: Box::new(NopClipboardContext::new().unwrap()), selection: None }
+        Self {
+            clipboard: Box::new(NopClipboardContext::new().unwrap()),
+            selection: None,
+            #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+            x11: None,
+            history: Default::default(),
+        }
     }
 }
 
 impl Default for Clipboard {
     fn default() -> Self {
         #[cfg(any(target_os = "macos", windows))]
-        return Self { clipboard: Box::new(ClipboardContext::new().unwrap()), selection: None };
+        return Self {
+            clipboard: Box::new(ClipboardContext::new().unwrap()),
+            selection: None,
+            history: Default::default(),
+        };
 
         #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
         return Self {
             clipboard: Box::new(ClipboardContext::new().unwrap()),
             selection: Some(Box::new(X11ClipboardContext::<X11SelectionClipboard>::new().unwrap())),
+            x11: SelectionOwner::new().ok(),
+            history: Default::default(),
         };
 
         #[cfg(not(any(feature = "x11", target_os = "macos", windows)))]
@@ -63,7 +92,22 @@ impl Default for Clipboard {
 }
 
 impl Clipboard {
-    pub fn store(&mut self, ty: ClipboardType, text: impl Into<String>) {
+    /// Store text of a selection which might still be changed by the mouse press `drag`.
+    ///
+    /// Consecutive stores of the same press replace each other in the history.
+    pub fn store_dragged(
+        &mut self,
+        ty: ClipboardType,
+        text: impl Into<String>,
+        drag: Option<usize>,
+    ) {
+        let text = text.into();
+        self.history.push_dragged(ty, &text, drag);
+        self.store_without_history(ty, text);
+    }
+
+    /// Store text without recording it in the clipboard history.
+    pub fn store_without_history(&mut self, ty: ClipboardType, text: impl Into<String>) {
         let clipboard = match (ty, &mut self.selection) {
             (ClipboardType::Selection, Some(provider)) => provider,
             (ClipboardType::Selection, None) => return,
@@ -75,6 +119,57 @@ impl Clipboard {
         });
     }
 
+    /// Store text with a specific MIME type, together with its plain text representation.
+    ///
+    /// Only the plain text is stored when custom MIME types are not supported. With
+    /// `record_history`, the text which can be pasted as plain text is added to the history.
+    pub fn store_with_mime_type(
+        &mut self,
+        ty: ClipboardType,
+        mime_type: &str,
+        text: String,
+        plain: String,
+        record_history: bool,
+    ) {
+        if mime_type.starts_with("text/plain") {
+            if record_history {
+                self.history.push(ty, &text);
+            }
+            self.store_without_history(ty, text);
+            return;
+        }
+
+        if record_history {
+            self.history.push(ty, &plain);
+        }
+
+        if !self.store_x11(ty, mime_type, &text, &plain) {
+            debug!("Unable to store {} in clipboard, storing plain text instead", mime_type);
+            self.store_without_history(ty, plain);
+        }
+    }
+
+    #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+    fn store_x11(&self, ty: ClipboardType, mime_type: &str, text: &str, plain: &str) -> bool {
+        let x11 = match &self.x11 {
+            Some(x11) => x11,
+            None => return false,
+        };
+
+        match x11.store(ty, mime_type, text, plain) {
+            Ok(()) => true,
+            Err(err) => {
+                warn!("Unable to store {} in clipboard: {}", mime_type, err);
+                false
+            },
+        }
+    }
+
+    #[cfg(not(all(feature = "x11", not(any(target_os = "macos", windows)))))]
+    fn store_x11(&self, _ty: ClipboardType, _mime_type: &str, _text: &str, _plain: &str) -> bool {
+        false
+    }
+
     pub fn load(&mut self, ty: ClipboardType) -> String {
         let clipboard = match (ty, &mut self.selection) {
             (ClipboardType::Selection, Some(provider)) => provider,
@@ -89,4 +184,185 @@ impl Clipboard {
             Ok(text) => text,
         }
     }
+
+    #[inline]
+    pub fn history(&self) -> &ClipboardHistory {
+        &self.history
+    }
+
+    #[inline]
+    pub fn history_mut(&mut self) -> &mut ClipboardHistory {
+        &mut self.history
+    }
+}
+
+/// Bounded history of text stored in the clipboard.
+#[derive(Default, Debug)]
+pub struct ClipboardHistory {
+    /// Stored text and its clipboard type, most recent entry first.
+    entries: VecDeque<(ClipboardType, String)>,
+
+    /// Maximum number of entries.
+    max_size: usize,
+
+    /// Index of the entry last restored by cycling through the history.
+    cycle_index: Option<usize>,
+
+    /// Mouse press which created the most recent entry.
+    drag: Option<usize>,
+}
+
+impl ClipboardHistory {
+    /// Set the maximum number of entries, dropping the oldest ones if necessary.
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size;
+        self.entries.truncate(max_size);
+    }
+
+    /// Get an entry by its age, with `0` being the most recent entry.
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(|(_, text)| text.as_str())
+    }
+
+    /// Iterate over all entries, starting with the most recent one.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(_, text)| text.as_str())
+    }
+
+    /// Advance to the next older entry, wrapping around to the most recent one.
+    pub fn cycle(&mut self) -> Option<&str> {
+        if self.entries.is_empty() {
+            return None;
+        }
+
+        let index = self.cycle_index.map_or(1, |index| index + 1) % self.entries.len();
+        self.cycle_index = Some(index);
+
+        self.get(index)
+    }
+
+    /// Record text stored in the clipboard.
+    fn push(&mut self, ty: ClipboardType, text: &str) {
+        self.push_dragged(ty, text, None);
+    }
+
+    /// Record text stored in the clipboard by the mouse press `drag`.
+    fn push_dragged(&mut self, ty: ClipboardType, text: &str, drag: Option<usize>) {
+        if text.is_empty() || self.max_size == 0 {
+            return;
+        }
+
+        self.cycle_index = None;
+
+        // Replace the last selection while it is still being dragged.
+        if drag.is_some() && drag == self.drag {
+            self.entries.pop_front();
+        }
+        self.drag = drag;
+
+        // Move duplicates to the front instead of adding them again.
+        self.entries.retain(|(_, entry)| entry != text);
+
+        self.entries.push_front((ty, text.to_owned()));
+        self.entries.truncate(self.max_size);
+    }
+}
+
+/// Check if a line of text looks like a password prompt.
+pub fn is_password_prompt(line: &str) -> bool {
+    let line = line.trim_end().to_lowercase();
+    if !line.ends_with(':') {
+        return false;
+    }
+
+    ["password", "passphrase", "passcode", "pin"]
+        .iter()
+        .any(|keyword| line.split(|c: char| !c.is_alphanumeric()).any(|word| word == *keyword))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_deduplication() {
+        let mut history = ClipboardHistory::default();
+        history.set_max_size(3);
+
+        history.push(ClipboardType::Clipboard, "a");
+        history.push(ClipboardType::Clipboard, "b");
+        history.push(ClipboardType::Clipboard, "a");
+        assert_eq!(history.iter().collect::<Vec<_>>(), vec!["a", "b"]);
+
+        history.push(ClipboardType::Clipboard, "c");
+        history.push(ClipboardType::Clipboard, "d");
+        assert_eq!(history.iter().collect::<Vec<_>>(), vec!["d", "c", "a"]);
+
+        history.push(ClipboardType::Clipboard, "");
+        assert_eq!(history.iter().count(), 3);
+    }
+
+    #[test]
+    fn history_selection_expansion() {
+        let mut history = ClipboardHistory::default();
+        history.set_max_size(10);
+
+        history.push(ClipboardType::Clipboard, "copied");
+        history.push_dragged(ClipboardType::Selection, "word", Some(0));
+        history.push_dragged(ClipboardType::Clipboard, "word and more", Some(0));
+        history.push_dragged(ClipboardType::Selection, "word and more", Some(0));
+        history.push_dragged(ClipboardType::Selection, "other", Some(1));
+        assert_eq!(history.iter().collect::<Vec<_>>(), vec!["other", "word and more", "copied"]);
+
+        // Selections of separate presses are kept, even if they contain each other.
+        history.push_dragged(ClipboardType::Selection, "other text", Some(2));
+        history.push(ClipboardType::Selection, "text");
+        history.push(ClipboardType::Selection, "more text");
+        assert_eq!(
+            history.iter().collect::<Vec<_>>(),
+            vec!["more text", "text", "other text", "other", "word and more", "copied"]
+        );
+    }
+
+    #[test]
+    fn formatted_history() {
+        let mut clipboard = Clipboard::new_nop();
+        clipboard.history_mut().set_max_size(10);
+
+        let ty = ClipboardType::Clipboard;
+        clipboard.store_with_mime_type(ty, "text/html", "<b>a</b>".into(), "a".into(), true);
+        clipboard.store_with_mime_type(ty, "text/rtf", "{b}".into(), "b".into(), false);
+        clipboard.store_with_mime_type(ty, "text/plain", "\x1b[1mc".into(), "c".into(), true);
+        clipboard.store_with_mime_type(ty, "text/plain", "d".into(), "d".into(), false);
+
+        assert_eq!(clipboard.history().iter().collect::<Vec<_>>(), vec!["\x1b[1mc", "a"]);
+    }
+
+    #[test]
+    fn history_cycle() {
+        let mut history = ClipboardHistory::default();
+        history.set_max_size(10);
+        assert_eq!(history.cycle(), None);
+
+        history.push(ClipboardType::Clipboard, "a");
+        history.push(ClipboardType::Clipboard, "b");
+        history.push(ClipboardType::Clipboard, "c");
+
+        assert_eq!(history.cycle(), Some("b"));
+        assert_eq!(history.cycle(), Some("a"));
+        assert_eq!(history.cycle(), Some("c"));
+
+        history.push(ClipboardType::Clipboard, "d");
+        assert_eq!(history.cycle(), Some("c"));
+    }
+
+    #[test]
+    fn password_prompt() {
+        assert!(is_password_prompt("Password: "));
+        assert!(is_password_prompt("[sudo] password for user:"));
+        assert!(is_password_prompt("Enter passphrase for key '/home/user/.ssh/id_ed25519':"));
+        assert!(is_password_prompt("Enter PIN:"));
+        assert!(!is_password_prompt("$ echo password"));
+        assert!(!is_password_prompt("Spinning:"));
+    }
 }
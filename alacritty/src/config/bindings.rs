@@ -5,6 +5,7 @@ use std::fmt::{self, Debug, Display};
 use bitflags::bitflags;
 use glutin::event::VirtualKeyCode::*;
 use glutin::event::{ModifiersState, MouseButton, VirtualKeyCode};
+use regex::Regex;
 use serde::de::Error as SerdeError;
 use serde::de::{self, MapAccess, Unexpected, Visitor};
 use serde::{Deserialize, Deserializer};
@@ -35,6 +36,15 @@ pub struct Binding<T> {
     /// Excluded binding modes where the binding won't be activated.
     pub notmode: BindingMode,
 
+    /// Keys which must be pressed in order before the trigger.
+    pub prefix: Vec<KeyPress>,
+
+    /// Key table which must be active for the binding to be triggered.
+    pub table: Option<String>,
+
+    /// Foreground process required to activate binding.
+    pub process: Option<ProcessCondition>,
+
     /// This property is used as part of the trigger detection code.
     ///
     /// For example, this might be a key like "G", or a mouse button.
@@ -66,6 +76,16 @@ impl<T: Eq> Binding<T> {
             return false;
         }
 
+        // Bindings in different key tables or with different key sequences never conflict.
+        if self.prefix != binding.prefix || self.table != binding.table {
+            return false;
+        }
+
+        // Bindings for different foreground processes can coexist.
+        if self.process != binding.process {
+            return false;
+        }
+
         let selfmode = if self.mode.is_empty() { BindingMode::all() } else { self.mode };
         let bindingmode = if binding.mode.is_empty() { BindingMode::all() } else { binding.mode };
 
@@ -81,8 +101,49 @@ impl<T: Eq> Binding<T> {
 
         true
     }
+
+    /// Check if the binding is active for the terminal's foreground process.
+    #[inline]
+    pub fn process_matches(&self, process: Option<&str>) -> bool {
+        match (&self.process, process) {
+            (Some(condition), Some(process)) => condition.matches(process),
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+}
+
+/// Condition on the name of the terminal's foreground process.
+#[derive(Debug, Clone)]
+pub enum ProcessCondition {
+    /// Process name must be equal to the string.
+    Name(String),
+
+    /// Process name must match the regex.
+    Regex(Regex),
+}
+
+impl ProcessCondition {
+    pub fn matches(&self, process: &str) -> bool {
+        match self {
+            ProcessCondition::Name(name) => name == process,
+            ProcessCondition::Regex(regex) => regex.is_match(process),
+        }
+    }
+}
+
+impl PartialEq for ProcessCondition {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ProcessCondition::Name(a), ProcessCondition::Name(b)) => a == b,
+            (ProcessCondition::Regex(a), ProcessCondition::Regex(b)) => a.as_str() == b.as_str(),
+            _ => false,
+        }
+    }
 }
 
+impl Eq for ProcessCondition {}
+
 #[derive(ConfigDeserialize, Debug, Clone, PartialEq, Eq)]
 pub enum Action {
     /// Write an escape sequence.
@@ -109,12 +170,64 @@ pub enum Action {
     #[config(skip)]
     SearchAction(SearchAction),
 
+    /// Perform mark mode action.
+    #[config(skip)]
+    MarkAction(MarkAction),
+
+    /// Paste an entry of the clipboard history, with `0` being the most recent one.
+    #[config(skip)]
+    PasteHistory(usize),
+
+    /// Activate a key table for the next key press.
+    #[config(skip)]
+    EnterKeyTable(String),
+
+    /// Activate a key table until it is left with `LeaveKeyTable`.
+    #[config(skip)]
+    LockKeyTable(String),
+
+    /// Leave the active key table.
+    LeaveKeyTable,
+
+    /// Run multiple actions in order.
+    #[config(skip)]
+    Sequence(Vec<Action>),
+
+    /// Start recording typed input into a named macro.
+    #[config(skip)]
+    RecordMacro(String),
+
+    /// Stop recording the active macro and save it.
+    StopMacroRecording,
+
+    /// Write the input recorded in a macro to the PTY.
+    #[config(skip)]
+    PlayMacro(String),
+
     /// Paste contents of system clipboard.
     Paste,
 
     /// Store current selection into clipboard.
     Copy,
 
+    /// Store current selection into clipboard, with colors as ANSI escape sequences.
+    CopyAnsi,
+
+    /// Store current selection into clipboard as HTML.
+    CopyHtml,
+
+    /// Store current selection into clipboard as RTF.
+    CopyRtf,
+
+    /// Save the entire scrollback history to a file.
+    SaveScrollback,
+
+    /// Save the visible screen to a file.
+    SaveScreen,
+
+    /// Open the scrollback history in `$PAGER` or `$EDITOR` in a new instance.
+    OpenScrollback,
+
     #[cfg(not(any(target_os = "macos", windows)))]
     /// Store current selection into selection buffer.
     CopySelection,
@@ -122,6 +235,18 @@ pub enum Action {
     /// Paste contents of selection buffer.
     PasteSelection,
 
+    /// Paste the output of the last hint command with captured output.
+    PasteHintOutput,
+
+    /// Show the clipboard history for selecting an entry to paste.
+    ShowClipboardHistory,
+
+    /// Store the next older clipboard history entry in the clipboard.
+    CycleClipboardHistory,
+
+    /// Show a searchable list of Unicode characters and emoji to type.
+    ShowCharacterPicker,
+
     /// Increase font size.
     IncreaseFontSize,
 
@@ -190,6 +315,9 @@ pub enum Action {
     /// Toggle vi mode.
     ToggleViMode,
 
+    /// Toggle mark mode for selecting text with the keyboard.
+    ToggleMarkMode,
+
     /// Allow receiving char input.
     ReceiveChar,
 
@@ -227,12 +355,45 @@ impl From<SearchAction> for Action {
     }
 }
 
+impl From<MarkAction> for Action {
+    fn from(action: MarkAction) -> Self {
+        Self::MarkAction(action)
+    }
+}
+
+/// Actions with an argument, written as a map with a single key.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+enum ArgumentAction {
+    Esc(String),
+    Command(Program),
+    PasteHistory(usize),
+    EnterKeyTable(String),
+    LockKeyTable(String),
+    RecordMacro(String),
+    PlayMacro(String),
+}
+
+impl From<ArgumentAction> for Action {
+    fn from(action: ArgumentAction) -> Self {
+        match action {
+            ArgumentAction::Esc(chars) => Self::Esc(chars),
+            ArgumentAction::Command(program) => Self::Command(program),
+            ArgumentAction::PasteHistory(index) => Self::PasteHistory(index),
+            ArgumentAction::EnterKeyTable(table) => Self::EnterKeyTable(table),
+            ArgumentAction::LockKeyTable(table) => Self::LockKeyTable(table),
+            ArgumentAction::RecordMacro(name) => Self::RecordMacro(name),
+            ArgumentAction::PlayMacro(name) => Self::PlayMacro(name),
+        }
+    }
+}
+
 /// Display trait used for error logging.
 impl Display for Action {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Action::ViMotion(motion) => motion.fmt(f),
             Action::ViAction(action) => action.fmt(f),
+            Action::MarkAction(action) => write!(f, "Mark{:?}", action),
             _ => write!(f, "{:?}", self),
         }
     }
@@ -280,6 +441,41 @@ pub enum SearchAction {
     SearchHistoryPrevious,
     /// Go to the next regex in the search history.
     SearchHistoryNext,
+    /// Toggle case insensitive matching.
+    SearchToggleCaseInsensitive,
+    /// Toggle smart case matching.
+    SearchToggleSmartCase,
+    /// Toggle literal matching of the search regex.
+    SearchToggleLiteral,
+    /// Toggle whole-word matching.
+    SearchToggleWholeWord,
+    /// Toggle the view showing only lines with a search match.
+    SearchToggleFilter,
+}
+
+/// Mark mode specific actions.
+///
+/// The names of these actions are prefixed with `Mark` in the configuration file.
+#[derive(ConfigDeserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MarkAction {
+    /// Extend the selection one cell to the left.
+    Left,
+    /// Extend the selection one cell to the right.
+    Right,
+    /// Extend the selection one line up.
+    Up,
+    /// Extend the selection one line down.
+    Down,
+    /// Extend the selection to the start of the previous word.
+    WordLeft,
+    /// Extend the selection to the end of the next word.
+    WordRight,
+    /// Select the word at the mark cursor.
+    SelectWord,
+    /// Select the line at the mark cursor.
+    SelectLine,
+    /// Copy the selection to the clipboard and leave mark mode.
+    Copy,
 }
 
 macro_rules! bindings {
@@ -331,6 +527,9 @@ macro_rules! bindings {
                 mods: _mods,
                 mode: _mode,
                 notmode: _notmode,
+                prefix: Vec::new(),
+                table: None,
+                process: None,
                 action: $action.into(),
             });
         )*
@@ -381,21 +580,29 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
             Action::Esc("\x1bOF".into());
         End,   ~BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH;
             Action::Esc("\x1b[F".into());
-        Up,    +BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH;
+        Up,    +BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH,
+            ~BindingMode::MARK;
             Action::Esc("\x1bOA".into());
-        Up,    ~BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH;
+        Up,    ~BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH,
+            ~BindingMode::MARK;
             Action::Esc("\x1b[A".into());
-        Down,  +BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH;
+        Down,  +BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH,
+            ~BindingMode::MARK;
             Action::Esc("\x1bOB".into());
-        Down,  ~BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH;
+        Down,  ~BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH,
+            ~BindingMode::MARK;
             Action::Esc("\x1b[B".into());
-        Right, +BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH;
+        Right, +BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH,
+            ~BindingMode::MARK;
             Action::Esc("\x1bOC".into());
-        Right, ~BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH;
+        Right, ~BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH,
+            ~BindingMode::MARK;
             Action::Esc("\x1b[C".into());
-        Left,  +BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH;
+        Left,  +BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH,
+            ~BindingMode::MARK;
             Action::Esc("\x1bOD".into());
-        Left,  ~BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH;
+        Left,  ~BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::SEARCH,
+            ~BindingMode::MARK;
             Action::Esc("\x1b[D".into());
         Back,        ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x7f".into());
         Insert,      ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[2~".into());
@@ -524,10 +731,28 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
         N,      ModifiersState::CTRL,  +BindingMode::SEARCH; SearchAction::SearchHistoryNext;
         Up,                            +BindingMode::SEARCH; SearchAction::SearchHistoryPrevious;
         Down,                          +BindingMode::SEARCH; SearchAction::SearchHistoryNext;
+        C,      ModifiersState::ALT,   +BindingMode::SEARCH;
+            SearchAction::SearchToggleCaseInsensitive;
+        S,      ModifiersState::ALT,   +BindingMode::SEARCH; SearchAction::SearchToggleSmartCase;
+        R,      ModifiersState::ALT,   +BindingMode::SEARCH; SearchAction::SearchToggleLiteral;
+        W,      ModifiersState::ALT,   +BindingMode::SEARCH; SearchAction::SearchToggleWholeWord;
+        F,      ModifiersState::ALT,   +BindingMode::SEARCH; SearchAction::SearchToggleFilter;
         Return,                        +BindingMode::SEARCH, ~BindingMode::VI;
             SearchAction::SearchFocusNext;
         Return, ModifiersState::SHIFT, +BindingMode::SEARCH, ~BindingMode::VI;
             SearchAction::SearchFocusPrevious;
+        Left,   ModifiersState::SHIFT, +BindingMode::MARK; MarkAction::Left;
+        Right,  ModifiersState::SHIFT, +BindingMode::MARK; MarkAction::Right;
+        Up,     ModifiersState::SHIFT, +BindingMode::MARK; MarkAction::Up;
+        Down,   ModifiersState::SHIFT, +BindingMode::MARK; MarkAction::Down;
+        Left,   ModifiersState::SHIFT | ModifiersState::CTRL, +BindingMode::MARK;
+            MarkAction::WordLeft;
+        Right,  ModifiersState::SHIFT | ModifiersState::CTRL, +BindingMode::MARK;
+            MarkAction::WordRight;
+        W,                             +BindingMode::MARK; MarkAction::SelectWord;
+        L,                             +BindingMode::MARK; MarkAction::SelectLine;
+        Return,                        +BindingMode::MARK; MarkAction::Copy;
+        Escape,                        +BindingMode::MARK; Action::ToggleMarkMode;
     );
 
     //   Code     Modifiers
@@ -556,55 +781,55 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
         let modifiers_code = index + 2;
         bindings.extend(bindings!(
             KeyBinding;
-            Delete, mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            Delete, mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[3;{}~", modifiers_code));
-            Up,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            Up,     mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[1;{}A", modifiers_code));
-            Down,   mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            Down,   mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[1;{}B", modifiers_code));
-            Right,  mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            Right,  mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[1;{}C", modifiers_code));
-            Left,   mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            Left,   mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[1;{}D", modifiers_code));
-            F1,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F1,     mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[1;{}P", modifiers_code));
-            F2,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F2,     mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[1;{}Q", modifiers_code));
-            F3,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F3,     mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[1;{}R", modifiers_code));
-            F4,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F4,     mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[1;{}S", modifiers_code));
-            F5,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F5,     mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[15;{}~", modifiers_code));
-            F6,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F6,     mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[17;{}~", modifiers_code));
-            F7,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F7,     mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[18;{}~", modifiers_code));
-            F8,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F8,     mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[19;{}~", modifiers_code));
-            F9,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F9,     mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[20;{}~", modifiers_code));
-            F10,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F10,    mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[21;{}~", modifiers_code));
-            F11,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F11,    mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[23;{}~", modifiers_code));
-            F12,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F12,    mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[24;{}~", modifiers_code));
-            F13,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F13,    mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[25;{}~", modifiers_code));
-            F14,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F14,    mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[26;{}~", modifiers_code));
-            F15,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F15,    mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[28;{}~", modifiers_code));
-            F16,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F16,    mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[29;{}~", modifiers_code));
-            F17,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F17,    mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[31;{}~", modifiers_code));
-            F18,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F18,    mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[32;{}~", modifiers_code));
-            F19,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F19,    mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[33;{}~", modifiers_code));
-            F20,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+            F20,    mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                 Action::Esc(format!("\x1b[34;{}~", modifiers_code));
         ));
 
@@ -612,15 +837,15 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
         if modifiers_code != 2 {
             bindings.extend(bindings!(
                 KeyBinding;
-                Insert,   mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+                Insert,   mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                     Action::Esc(format!("\x1b[2;{}~", modifiers_code));
-                PageUp,   mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+                PageUp,   mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                     Action::Esc(format!("\x1b[5;{}~", modifiers_code));
-                PageDown, mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+                PageDown, mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                     Action::Esc(format!("\x1b[6;{}~", modifiers_code));
-                End,      mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+                End,      mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                     Action::Esc(format!("\x1b[1;{}F", modifiers_code));
-                Home,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+                Home,     mods, ~BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::MARK;
                     Action::Esc(format!("\x1b[1;{}H", modifiers_code));
             ));
         }
@@ -643,6 +868,8 @@ fn common_keybindings() -> Vec<KeyBinding> {
             Action::SearchBackward;
         C,        ModifiersState::CTRL | ModifiersState::SHIFT,
             +BindingMode::VI, ~BindingMode::SEARCH; Action::ClearSelection;
+        M,        ModifiersState::CTRL | ModifiersState::SHIFT,
+            ~BindingMode::VI, ~BindingMode::SEARCH; Action::ToggleMarkMode;
         Insert,   ModifiersState::SHIFT, ~BindingMode::VI; Action::PasteSelection;
         Key0,     ModifiersState::CTRL;  Action::ResetFontSize;
         Equals,   ModifiersState::CTRL;  Action::IncreaseFontSize;
@@ -691,6 +918,8 @@ pub fn platform_key_bindings() -> Vec<KeyBinding> {
         H, ModifiersState::LOGO; Action::Hide;
         H, ModifiersState::LOGO | ModifiersState::ALT; Action::HideOtherApplications;
         M, ModifiersState::LOGO; Action::Minimize;
+        M, ModifiersState::LOGO | ModifiersState::SHIFT, ~BindingMode::VI, ~BindingMode::SEARCH;
+            Action::ToggleMarkMode;
         Q, ModifiersState::LOGO; Action::Quit;
         W, ModifiersState::LOGO; Action::Quit;
         F, ModifiersState::LOGO, ~BindingMode::SEARCH; Action::SearchForward;
@@ -739,17 +968,19 @@ bitflags! {
         const ALT_SCREEN          = 0b0000_0100;
         const VI                  = 0b0000_1000;
         const SEARCH              = 0b0001_0000;
+        const MARK                = 0b0010_0000;
     }
 }
 
 impl BindingMode {
-    pub fn new(mode: &TermMode, search: bool) -> BindingMode {
+    pub fn new(mode: &TermMode, search: bool, mark: bool) -> BindingMode {
         let mut binding_mode = BindingMode::empty();
         binding_mode.set(BindingMode::APP_CURSOR, mode.contains(TermMode::APP_CURSOR));
         binding_mode.set(BindingMode::APP_KEYPAD, mode.contains(TermMode::APP_KEYPAD));
         binding_mode.set(BindingMode::ALT_SCREEN, mode.contains(TermMode::ALT_SCREEN));
         binding_mode.set(BindingMode::VI, mode.contains(TermMode::VI));
         binding_mode.set(BindingMode::SEARCH, search);
+        binding_mode.set(BindingMode::MARK, mark);
         binding_mode
     }
 }
@@ -789,6 +1020,8 @@ impl<'a> Deserialize<'a> for ModeWrapper {
                         "~vi" => res.not_mode |= BindingMode::VI,
                         "search" => res.mode |= BindingMode::SEARCH,
                         "~search" => res.not_mode |= BindingMode::SEARCH,
+                        "mark" => res.mode |= BindingMode::MARK,
+                        "~mark" => res.not_mode |= BindingMode::MARK,
                         _ => return Err(E::invalid_value(Unexpected::Str(modifier), &self)),
                     }
                 }
@@ -858,35 +1091,44 @@ struct RawBinding {
     mods: ModifiersState,
     mode: BindingMode,
     notmode: BindingMode,
+    prefix: Vec<KeyPress>,
+    table: Option<String>,
+    process: Option<ProcessCondition>,
     action: Action,
 }
 
 impl RawBinding {
-    fn into_mouse_binding(self) -> Result<MouseBinding, Self> {
+    fn into_mouse_binding(self) -> Option<MouseBinding> {
         if let Some(mouse) = self.mouse {
-            Ok(Binding {
+            Some(Binding {
                 trigger: mouse,
                 mods: self.mods,
                 action: self.action,
                 mode: self.mode,
                 notmode: self.notmode,
+                prefix: self.prefix,
+                table: self.table,
+                process: self.process,
             })
         } else {
-            Err(self)
+            None
         }
     }
 
-    fn into_key_binding(self) -> Result<KeyBinding, Self> {
+    fn into_key_binding(self) -> Option<KeyBinding> {
         if let Some(key) = self.key {
-            Ok(KeyBinding {
+            Some(KeyBinding {
                 trigger: key,
                 mods: self.mods,
                 action: self.action,
                 mode: self.mode,
                 notmode: self.notmode,
+                prefix: self.prefix,
+                table: self.table,
+                process: self.process,
             })
         } else {
-            Err(self)
+            None
         }
     }
 }
@@ -896,7 +1138,19 @@ impl<'a> Deserialize<'a> for RawBinding {
     where
         D: Deserializer<'a>,
     {
-        const FIELDS: &[&str] = &["key", "mods", "mode", "action", "chars", "mouse", "command"];
+        const FIELDS: &[&str] = &[
+            "key",
+            "mods",
+            "mode",
+            "action",
+            "chars",
+            "mouse",
+            "command",
+            "prefix",
+            "table",
+            "process",
+            "process_regex",
+        ];
 
         enum Field {
             Key,
@@ -906,6 +1160,10 @@ impl<'a> Deserialize<'a> for RawBinding {
             Chars,
             Mouse,
             Command,
+            Prefix,
+            Table,
+            Process,
+            ProcessRegex,
         }
 
         impl<'a> Deserialize<'a> for Field {
@@ -934,6 +1192,10 @@ impl<'a> Deserialize<'a> for RawBinding {
                             "chars" => Ok(Field::Chars),
                             "mouse" => Ok(Field::Mouse),
                             "command" => Ok(Field::Command),
+                            "prefix" => Ok(Field::Prefix),
+                            "table" => Ok(Field::Table),
+                            "process" => Ok(Field::Process),
+                            "process_regex" => Ok(Field::ProcessRegex),
                             _ => Err(E::unknown_field(value, FIELDS)),
                         }
                     }
@@ -963,6 +1225,9 @@ impl<'a> Deserialize<'a> for RawBinding {
                 let mut not_mode: Option<BindingMode> = None;
                 let mut mouse: Option<MouseButton> = None;
                 let mut command: Option<Program> = None;
+                let mut prefix: Option<Vec<KeyPress>> = None;
+                let mut table: Option<String> = None;
+                let mut process: Option<ProcessCondition> = None;
 
                 use de::Error;
 
@@ -1009,39 +1274,15 @@ impl<'a> Deserialize<'a> for RawBinding {
                                 return Err(<V::Error as Error>::duplicate_field("action"));
                             }
 
-                            let value = map.next_value::<SerdeValue>()?;
-
-                            action = if let Ok(vi_action) = ViAction::deserialize(value.clone()) {
-                                Some(vi_action.into())
-                            } else if let Ok(vi_motion) = ViMotion::deserialize(value.clone()) {
-                                Some(vi_motion.into())
-                            } else if let Ok(search_action) =
-                                SearchAction::deserialize(value.clone())
-                            {
-                                Some(search_action.into())
-                            } else {
-                                match Action::deserialize(value.clone()).map_err(V::Error::custom) {
-                                    Ok(action) => Some(action),
-                                    Err(err) => {
-                                        let value = match value {
-                                            SerdeValue::String(string) => string,
-                                            SerdeValue::Mapping(map) if map.len() == 1 => {
-                                                match map.into_iter().next() {
-                                                    Some((
-                                                        SerdeValue::String(string),
-                                                        SerdeValue::Null,
-                                                    )) => string,
-                                                    _ => return Err(err),
-                                                }
-                                            },
-                                            _ => return Err(err),
-                                        };
-                                        return Err(V::Error::custom(format!(
-                                            "unknown keyboard action `{}`",
-                                            value
-                                        )));
-                                    },
-                                }
+                            action = match map.next_value::<SerdeValue>()? {
+                                SerdeValue::Sequence(values) => {
+                                    let actions = values
+                                        .into_iter()
+                                        .map(deserialize_action)
+                                        .collect::<Result<_, _>>()?;
+                                    Some(Action::Sequence(actions))
+                                },
+                                value => Some(deserialize_action(value)?),
                             };
                         },
                         Field::Chars => {
@@ -1065,6 +1306,36 @@ impl<'a> Deserialize<'a> for RawBinding {
 
                             command = Some(map.next_value::<Program>()?);
                         },
+                        Field::Prefix => {
+                            if prefix.is_some() {
+                                return Err(<V::Error as Error>::duplicate_field("prefix"));
+                            }
+
+                            prefix = Some(map.next_value()?);
+                        },
+                        Field::Table => {
+                            if table.is_some() {
+                                return Err(<V::Error as Error>::duplicate_field("table"));
+                            }
+
+                            table = Some(map.next_value()?);
+                        },
+                        Field::Process | Field::ProcessRegex => {
+                            if process.is_some() {
+                                return Err(V::Error::custom(
+                                    "must specify only one of process or process_regex",
+                                ));
+                            }
+
+                            let value: String = map.next_value()?;
+                            process = Some(match struct_key {
+                                Field::ProcessRegex => {
+                                    let regex = Regex::new(&value).map_err(V::Error::custom)?;
+                                    ProcessCondition::Regex(regex)
+                                },
+                                _ => ProcessCondition::Name(value),
+                            });
+                        },
                     }
                 }
 
@@ -1073,28 +1344,10 @@ impl<'a> Deserialize<'a> for RawBinding {
                 let mods = mods.unwrap_or_else(ModifiersState::default);
 
                 let action = match (action, chars, command) {
-                    (Some(action @ Action::ViMotion(_)), None, None)
-                    | (Some(action @ Action::ViAction(_)), None, None) => {
-                        if !mode.intersects(BindingMode::VI) || not_mode.intersects(BindingMode::VI)
-                        {
-                            return Err(V::Error::custom(format!(
-                                "action `{}` is only available in vi mode, try adding `mode: Vi`",
-                                action,
-                            )));
-                        }
-                        action
-                    },
-                    (Some(action @ Action::SearchAction(_)), None, None) => {
-                        if !mode.intersects(BindingMode::SEARCH) {
-                            return Err(V::Error::custom(format!(
-                                "action `{}` is only available in search mode, try adding `mode: \
-                                 Search`",
-                                action,
-                            )));
-                        }
+                    (Some(action), None, None) => {
+                        check_action_mode(&action, mode, not_mode).map_err(V::Error::custom)?;
                         action
                     },
-                    (Some(action), None, None) => action,
                     (None, Some(chars), None) => Action::Esc(chars),
                     (None, None, Some(cmd)) => Action::Command(cmd),
                     _ => {
@@ -1108,7 +1361,21 @@ impl<'a> Deserialize<'a> for RawBinding {
                     return Err(V::Error::custom("bindings require mouse button or key"));
                 }
 
-                Ok(RawBinding { mode, notmode: not_mode, action, key, mouse, mods })
+                if mouse.is_some() && (prefix.is_some() || table.is_some()) {
+                    return Err(V::Error::custom("prefix and table require a key binding"));
+                }
+
+                Ok(RawBinding {
+                    mode,
+                    notmode: not_mode,
+                    action,
+                    key,
+                    mouse,
+                    mods,
+                    prefix: prefix.unwrap_or_default(),
+                    table,
+                    process,
+                })
             }
         }
 
@@ -1116,6 +1383,74 @@ impl<'a> Deserialize<'a> for RawBinding {
     }
 }
 
+/// Deserialize a mark mode action from its `Mark` prefixed name.
+fn deserialize_mark_action(value: &SerdeValue) -> Option<MarkAction> {
+    let name = value.as_str()?;
+    match name.get(..4) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("mark") => {
+            MarkAction::deserialize(SerdeValue::String(name[4..].into())).ok()
+        },
+        _ => None,
+    }
+}
+
+/// Deserialize a single binding action.
+fn deserialize_action<E: de::Error>(value: SerdeValue) -> Result<Action, E> {
+    if let Ok(vi_action) = ViAction::deserialize(value.clone()) {
+        return Ok(vi_action.into());
+    } else if let Ok(vi_motion) = ViMotion::deserialize(value.clone()) {
+        return Ok(vi_motion.into());
+    } else if let Ok(search_action) = SearchAction::deserialize(value.clone()) {
+        return Ok(search_action.into());
+    } else if let Some(mark_action) = deserialize_mark_action(&value) {
+        return Ok(mark_action.into());
+    } else if let Ok(action) = ArgumentAction::deserialize(value.clone()) {
+        return Ok(action.into());
+    }
+
+    match Action::deserialize(value.clone()).map_err(E::custom) {
+        Ok(action) => Ok(action),
+        Err(err) => {
+            let value = match value {
+                SerdeValue::String(string) => string,
+                SerdeValue::Mapping(map) if map.len() == 1 => match map.into_iter().next() {
+                    Some((SerdeValue::String(string), SerdeValue::Null)) => string,
+                    _ => return Err(err),
+                },
+                _ => return Err(err),
+            };
+            Err(E::custom(format!("unknown keyboard action `{}`", value)))
+        },
+    }
+}
+
+/// Check that mode specific actions are only bound in their mode.
+fn check_action_mode(
+    action: &Action,
+    mode: BindingMode,
+    not_mode: BindingMode,
+) -> Result<(), String> {
+    match action {
+        Action::ViMotion(_) | Action::ViAction(_)
+            if !mode.intersects(BindingMode::VI) || not_mode.intersects(BindingMode::VI) =>
+        {
+            Err(format!("action `{}` is only available in vi mode, try adding `mode: Vi`", action))
+        },
+        Action::SearchAction(_) if !mode.intersects(BindingMode::SEARCH) => Err(format!(
+            "action `{}` is only available in search mode, try adding `mode: Search`",
+            action
+        )),
+        Action::MarkAction(_) if !mode.intersects(BindingMode::MARK) => Err(format!(
+            "action `{}` is only available in mark mode, try adding `mode: Mark`",
+            action
+        )),
+        Action::Sequence(actions) => {
+            actions.iter().try_for_each(|action| check_action_mode(action, mode, not_mode))
+        },
+        _ => Ok(()),
+    }
+}
+
 impl<'a> Deserialize<'a> for MouseBinding {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -1123,7 +1458,7 @@ impl<'a> Deserialize<'a> for MouseBinding {
     {
         let raw = RawBinding::deserialize(deserializer)?;
         raw.into_mouse_binding()
-            .map_err(|_| D::Error::custom("expected mouse binding, got key binding"))
+            .ok_or_else(|| D::Error::custom("expected mouse binding, got key binding"))
     }
 }
 
@@ -1134,7 +1469,36 @@ impl<'a> Deserialize<'a> for KeyBinding {
     {
         let raw = RawBinding::deserialize(deserializer)?;
         raw.into_key_binding()
-            .map_err(|_| D::Error::custom("expected key binding, got mouse binding"))
+            .ok_or_else(|| D::Error::custom("expected key binding, got mouse binding"))
+    }
+}
+
+/// Key with modifiers, pressed as part of a key sequence.
+#[derive(Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KeyPress {
+    pub key: Key,
+    #[serde(default)]
+    pub mods: ModsWrapper,
+}
+
+impl Display for KeyPress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mods = self.mods.into_inner();
+        let names = [
+            (ModifiersState::CTRL, "Ctrl"),
+            (ModifiersState::ALT, "Alt"),
+            (ModifiersState::SHIFT, "Shift"),
+            (ModifiersState::LOGO, "Super"),
+        ];
+
+        for (_, name) in names.iter().filter(|(modifier, _)| mods.contains(*modifier)) {
+            write!(f, "{}+", name)?;
+        }
+
+        match self.key {
+            Key::Keycode(key) => write!(f, "{:?}", key),
+            Key::Scancode(scancode) => write!(f, "{:#x}", scancode),
+        }
     }
 }
 
@@ -1204,6 +1568,9 @@ mod tests {
                 action: Action::None,
                 mode: BindingMode::empty(),
                 notmode: BindingMode::empty(),
+                prefix: Vec::new(),
+                table: None,
+                process: None,
                 trigger: Default::default(),
             }
         }
@@ -1394,4 +1761,59 @@ mod tests {
         assert!(!binding.is_triggered_by(BindingMode::ALT_SCREEN, mods, &t));
         assert!(!binding.is_triggered_by(BindingMode::ALT_SCREEN | BindingMode::VI, mods, &t));
     }
+
+    #[test]
+    fn binding_mismatches_process() {
+        let mut binding = MockBinding::default();
+        let mut other = MockBinding::default();
+        binding.process = Some(ProcessCondition::Name(String::from("vim")));
+
+        assert!(!binding.triggers_match(&other));
+
+        other.process = Some(ProcessCondition::Name(String::from("vim")));
+        assert!(binding.triggers_match(&other));
+    }
+
+    #[test]
+    fn binding_process_condition() {
+        let binding = MockBinding {
+            process: Some(ProcessCondition::Regex(Regex::new("^n?vim$").unwrap())),
+            ..MockBinding::default()
+        };
+
+        assert!(binding.process_matches(Some("nvim")));
+        assert!(!binding.process_matches(Some("zsh")));
+        assert!(!binding.process_matches(None));
+        assert!(MockBinding::default().process_matches(None));
+    }
+
+    #[test]
+    fn deserialize_process_condition() {
+        let binding: RawBinding =
+            serde_yaml::from_str("{ key: A, process_regex: '^vim$', chars: 'a' }").unwrap();
+        assert!(binding.process.unwrap().matches("vim"));
+
+        let binding: RawBinding =
+            serde_yaml::from_str("{ key: A, process: vim, chars: 'a' }").unwrap();
+        assert_eq!(binding.process, Some(ProcessCondition::Name(String::from("vim"))));
+
+        let binding = serde_yaml::from_str::<RawBinding>(
+            "{ key: A, process: vim, process_regex: vim, chars: 'a' }",
+        );
+        assert!(binding.is_err());
+    }
+
+    #[test]
+    fn deserialize_mark_action() {
+        let binding: RawBinding =
+            serde_yaml::from_str("{ key: A, mode: Mark, action: MarkWordLeft }").unwrap();
+        assert_eq!(binding.action, Action::MarkAction(MarkAction::WordLeft));
+        assert_eq!(binding.action.to_string(), "MarkWordLeft");
+
+        let binding: RawBinding = serde_yaml::from_str("{ key: A, action: Copy }").unwrap();
+        assert_eq!(binding.action, Action::Copy);
+
+        let binding = serde_yaml::from_str::<RawBinding>("{ key: A, mode: Mark, action: Mark }");
+        assert!(binding.is_err());
+    }
 }
@@ -0,0 +1,20 @@
+use alacritty_config_derive::ConfigDeserialize;
+
+/// Default number of clipboard history entries.
+const DEFAULT_SIZE: usize = 50;
+
+/// History of text stored in the clipboard.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ClipboardHistoryConfig {
+    /// Maximum number of entries kept in the history.
+    pub size: usize,
+
+    /// Do not record text copied while the cursor is at a password prompt.
+    pub exclude_password_prompts: bool,
+}
+
+impl Default for ClipboardHistoryConfig {
+    fn default() -> Self {
+        Self { size: DEFAULT_SIZE, exclude_password_prompts: true }
+    }
+}
@@ -39,6 +39,7 @@ pub struct LineIndicatorColors {
 pub struct HintColors {
     pub start: HintStartColors,
     pub end: HintEndColors,
+    pub selected: HintSelectedColors,
 }
 
 #[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
@@ -71,6 +72,21 @@ impl Default for HintEndColors {
     }
 }
 
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HintSelectedColors {
+    pub foreground: CellRgb,
+    pub background: CellRgb,
+}
+
+impl Default for HintSelectedColors {
+    fn default() -> Self {
+        Self {
+            foreground: CellRgb::Rgb(Rgb { r: 0x1d, g: 0x1f, b: 0x21 }),
+            background: CellRgb::Rgb(Rgb { r: 0x5e, g: 0xc4, b: 0xff }),
+        }
+    }
+}
+
 #[derive(Deserialize, Copy, Clone, Default, Debug, PartialEq, Eq)]
 pub struct IndexedColor {
     pub color: Rgb,
@@ -0,0 +1,30 @@
+use alacritty_config_derive::ConfigDeserialize;
+
+/// Handling of files dropped onto the window.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DragAndDropConfig {
+    /// Quoting used for escaping the paths of dropped files.
+    pub quoting: PathQuoting,
+
+    /// Insert paths relative to the working directory of the foreground process.
+    pub relative_paths: bool,
+}
+
+impl Default for DragAndDropConfig {
+    fn default() -> Self {
+        Self { quoting: PathQuoting::Single, relative_paths: false }
+    }
+}
+
+/// Shell quoting style for dropped file paths.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PathQuoting {
+    /// Wrap paths in single quotes.
+    Single,
+
+    /// Wrap paths in double quotes.
+    Double,
+
+    /// Escape special characters with backslashes.
+    Backslash,
+}
@@ -0,0 +1,20 @@
+use alacritty_config_derive::ConfigDeserialize;
+
+/// Default path template for saved terminal content.
+const DEFAULT_PATH: &str = "~/alacritty-{timestamp}.txt";
+
+/// Configuration for saving terminal content to a file.
+#[derive(ConfigDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ExportConfig {
+    /// Path template for the saved file.
+    pub path: String,
+
+    /// Preserve colors and text attributes using SGR escapes.
+    pub escapes: bool,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self { path: DEFAULT_PATH.into(), escapes: false }
+    }
+}
@@ -10,10 +10,16 @@ use serde_yaml::Value;
 use alacritty_terminal::config::{Config as TermConfig, LOG_TARGET_CONFIG};
 
 pub mod bell;
+pub mod clipboard_history;
 pub mod color;
 pub mod debug;
+pub mod drag_and_drop;
+pub mod export;
 pub mod font;
 pub mod monitor;
+pub mod osc52;
+pub mod paste;
+pub mod search;
 pub mod serde_utils;
 pub mod ui_config;
 pub mod window;
@@ -22,7 +28,11 @@ mod bindings;
 mod mouse;
 
 use crate::cli::Options;
-pub use crate::config::bindings::{Action, Binding, BindingMode, Key, SearchAction, ViAction};
+#[cfg(test)]
+pub use crate::config::bindings::ModsWrapper;
+pub use crate::config::bindings::{
+    Action, Binding, BindingMode, Key, KeyPress, MarkAction, SearchAction, ViAction,
+};
 #[cfg(test)]
 pub use crate::config::mouse::{ClickHandler, Mouse};
 use crate::config::ui_config::UiConfig;
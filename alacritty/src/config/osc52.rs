@@ -0,0 +1,67 @@
+use alacritty_config_derive::ConfigDeserialize;
+use alacritty_terminal::term::ClipboardType;
+
+/// Default maximum size of text stored through OSC 52 in bytes.
+const DEFAULT_MAX_STORE_SIZE: usize = 1024 * 1024;
+
+/// Clipboard access by applications through the OSC 52 escape sequence.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Osc52Config {
+    /// Access to the system clipboard.
+    pub clipboard: Osc52Permissions,
+
+    /// Access to the selection buffer.
+    pub selection: Osc52Permissions,
+
+    /// Maximum size of text stored by a single escape sequence in bytes.
+    pub max_store_size: usize,
+}
+
+impl Default for Osc52Config {
+    fn default() -> Self {
+        Self {
+            clipboard: Default::default(),
+            selection: Default::default(),
+            max_store_size: DEFAULT_MAX_STORE_SIZE,
+        }
+    }
+}
+
+impl Osc52Config {
+    /// Permissions for a specific clipboard.
+    pub fn permissions(&self, ty: ClipboardType) -> Osc52Permissions {
+        match ty {
+            ClipboardType::Clipboard => self.clipboard,
+            ClipboardType::Selection => self.selection,
+        }
+    }
+}
+
+/// Policies for reading and writing a clipboard.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Osc52Permissions {
+    /// Policy for sending the clipboard content to the application.
+    pub read: Osc52Policy,
+
+    /// Policy for storing text in the clipboard.
+    pub write: Osc52Policy,
+}
+
+impl Default for Osc52Permissions {
+    fn default() -> Self {
+        Self { read: Osc52Policy::Ask, write: Osc52Policy::Allow }
+    }
+}
+
+/// Handling of OSC 52 clipboard requests.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Osc52Policy {
+    /// Answer requests without asking.
+    Allow,
+
+    /// Ignore all requests.
+    Deny,
+
+    /// Ask for confirmation in the message bar.
+    Ask,
+}
@@ -0,0 +1,11 @@
+use alacritty_config_derive::ConfigDeserialize;
+
+/// Handling of text pasted into the terminal.
+#[derive(ConfigDeserialize, Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub struct PasteConfig {
+    /// Ask for confirmation before pasting multiple lines or control characters.
+    pub confirm: bool,
+
+    /// Remove escape sequences and control characters from pasted text.
+    pub sanitize: bool,
+}
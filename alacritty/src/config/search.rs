@@ -0,0 +1,19 @@
+use alacritty_config_derive::ConfigDeserialize;
+
+use alacritty_terminal::term::search::DEFAULT_DFA_SIZE_LIMIT;
+
+/// Regex search configuration.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SearchConfig {
+    /// Maximum size of each compiled search regex in bytes.
+    pub dfa_size_limit: usize,
+
+    /// Share search history between sessions.
+    pub persistent_history: bool,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self { dfa_size_limit: DEFAULT_DFA_SIZE_LIMIT, persistent_history: true }
+    }
+}
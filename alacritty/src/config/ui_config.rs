@@ -1,27 +1,43 @@
 use std::cell::RefCell;
+use std::mem;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::Duration;
 
 use glutin::event::{ModifiersState, VirtualKeyCode};
 use log::error;
+use regex::Regex;
 use serde::de::Error as SerdeError;
 use serde::{self, Deserialize, Deserializer};
 use unicode_width::UnicodeWidthChar;
 
 use alacritty_config_derive::ConfigDeserialize;
 use alacritty_terminal::config::{Percentage, Program, LOG_TARGET_CONFIG};
-use alacritty_terminal::term::search::RegexSearch;
+use alacritty_terminal::term::search::{
+    CancelHandle, RegexError, RegexSearch, DEFAULT_DFA_SIZE_LIMIT,
+};
+use alacritty_terminal::thread;
 
 use crate::config::bell::BellConfig;
 use crate::config::bindings::{
     self, Action, Binding, BindingMode, Key, KeyBinding, ModsWrapper, MouseBinding,
 };
+use crate::config::clipboard_history::ClipboardHistoryConfig;
 use crate::config::color::Colors;
 use crate::config::debug::Debug;
+use crate::config::drag_and_drop::DragAndDropConfig;
+use crate::config::export::ExportConfig;
 use crate::config::font::Font;
 use crate::config::mouse::Mouse;
+use crate::config::osc52::Osc52Config;
+use crate::config::paste::PasteConfig;
+use crate::config::search::SearchConfig;
 use crate::config::window::WindowConfig;
 
+/// Default time in milliseconds before a pending key sequence is aborted.
+const DEFAULT_KEY_SEQUENCE_TIMEOUT: u64 = 1000;
+
 /// Regex used for the default URL hint.
 #[rustfmt::skip]
 const URL_REGEX: &str = "(ipfs:|ipns:|magnet:|mailto:|gemini:|gopher:|https:|http:|news:|file:|git:|ssh:|ftp:)\
@@ -59,9 +75,27 @@ pub struct UiConfig {
     #[config(skip)]
     pub config_paths: Vec<PathBuf>,
 
+    /// Regex search configuration.
+    pub search: SearchConfig,
+
     /// Regex hints for interacting with terminal content.
     pub hints: Hints,
 
+    /// Saving terminal content to a file.
+    pub export: ExportConfig,
+
+    /// Handling of pasted text.
+    pub paste: PasteConfig,
+
+    /// History of text stored in the clipboard.
+    pub clipboard_history: ClipboardHistoryConfig,
+
+    /// Clipboard access through OSC 52 escape sequences.
+    pub osc52: Osc52Config,
+
+    /// Handling of files dropped onto the window.
+    pub drag_and_drop: DragAndDropConfig,
+
     /// Keybindings.
     key_bindings: KeyBindings,
 
@@ -70,6 +104,9 @@ pub struct UiConfig {
 
     /// Background opacity from 0.0 to 1.0.
     background_opacity: Percentage,
+
+    /// Time in milliseconds before a pending key sequence is aborted.
+    key_sequence_timeout: u64,
 }
 
 impl Default for UiConfig {
@@ -85,10 +122,17 @@ impl Default for UiConfig {
             key_bindings: Default::default(),
             mouse_bindings: Default::default(),
             background_opacity: Default::default(),
+            key_sequence_timeout: DEFAULT_KEY_SEQUENCE_TIMEOUT,
             bell: Default::default(),
             colors: Default::default(),
             draw_bold_text_with_bright_colors: Default::default(),
             hints: Default::default(),
+            search: Default::default(),
+            export: Default::default(),
+            paste: Default::default(),
+            clipboard_history: Default::default(),
+            osc52: Default::default(),
+            drag_and_drop: Default::default(),
         }
     }
 }
@@ -107,6 +151,9 @@ impl UiConfig {
                 mods: binding.mods.0,
                 mode: BindingMode::empty(),
                 notmode: BindingMode::empty(),
+                prefix: Vec::new(),
+                table: None,
+                process: None,
                 action: Action::Hint(hint.clone()),
             };
 
@@ -119,6 +166,15 @@ impl UiConfig {
         self.background_opacity.as_f32()
     }
 
+    /// Timeout of pending key sequences and key tables, if enabled.
+    #[inline]
+    pub fn key_sequence_timeout(&self) -> Option<Duration> {
+        match self.key_sequence_timeout {
+            0 => None,
+            timeout => Some(Duration::from_millis(timeout)),
+        }
+    }
+
     #[inline]
     pub fn key_bindings(&self) -> &[KeyBinding] {
         &self.key_bindings.0.as_slice()
@@ -215,13 +271,18 @@ pub struct Hints {
 
     /// All configured terminal hints.
     pub enabled: Vec<Hint>,
+
+    /// Maximum size of each compiled hint regex in bytes.
+    dfa_size_limit: usize,
+
+    /// Separator used to join the text of multiple selected matches.
+    pub separator: String,
 }
 
 impl Default for Hints {
     fn default() -> Self {
         // Add URL hint by default when no other hint is present.
-        let pattern = LazyRegexVariant::Pattern(String::from(URL_REGEX));
-        let regex = LazyRegex(Rc::new(RefCell::new(pattern)));
+        let regex = LazyRegex::from(URL_REGEX);
 
         #[cfg(not(any(target_os = "macos", windows)))]
         let action = HintAction::Command(Program::Just(String::from("xdg-open")));
@@ -238,6 +299,9 @@ impl Default for Hints {
                 regex,
                 action,
                 post_processing: true,
+                scrollback: false,
+                multi_select: false,
+                capture_output: false,
                 mouse: Some(HintMouse { enabled: true, mods: Default::default() }),
                 binding: Some(HintBinding {
                     key: Key::Keycode(VirtualKeyCode::U),
@@ -245,6 +309,8 @@ impl Default for Hints {
                 }),
             }],
             alphabet: Default::default(),
+            separator: String::from(" "),
+            dfa_size_limit: DEFAULT_DFA_SIZE_LIMIT,
         }
     }
 }
@@ -254,6 +320,18 @@ impl Hints {
     pub fn alphabet(&self) -> &str {
         &self.alphabet.0
     }
+
+    /// Start compiling all hint regexes on separate threads.
+    ///
+    /// The `notify` callback is run on the compiling thread once a regex is ready for use.
+    pub fn compile_in_background<F>(&self, notify: F)
+    where
+        F: Fn() + Clone + Send + 'static,
+    {
+        for hint in &self.enabled {
+            hint.regex.compile_in_background(self.dfa_size_limit, notify.clone());
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -327,6 +405,18 @@ pub struct Hint {
     #[serde(default)]
     pub post_processing: bool,
 
+    /// Search for matches in the entire scrollback history.
+    #[serde(default)]
+    pub scrollback: bool,
+
+    /// Toggle multiple matches before running the action.
+    #[serde(default)]
+    pub multi_select: bool,
+
+    /// Show the output of the hint command.
+    #[serde(default)]
+    pub capture_output: bool,
+
     /// Hint mouse highlighting.
     pub mouse: Option<HintMouse>,
 
@@ -353,16 +443,57 @@ pub struct HintMouse {
 }
 
 /// Lazy regex with interior mutability.
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct LazyRegex(Rc<RefCell<LazyRegexVariant>>);
+#[derive(Clone, Debug)]
+pub struct LazyRegex(Rc<LazyRegexState>);
 
 impl LazyRegex {
     /// Execute a function with the compiled regex DFAs as parameter.
-    pub fn with_compiled<T, F>(&self, mut f: F) -> T
+    ///
+    /// Returns `None` without blocking until the compilation started by
+    /// [`LazyRegex::compile_in_background`] has finished.
+    pub fn with_compiled<T, F>(&self, f: F) -> Option<T>
     where
         F: FnMut(&RegexSearch) -> T,
     {
-        f(self.0.borrow_mut().compiled())
+        self.0.variant.borrow_mut().compiled().map(f)
+    }
+
+    /// Execute a function with the regex anchored to match an entire text, for extracting the
+    /// capture groups of a match.
+    ///
+    /// The regex is compiled on first use and `None` is returned if it is invalid.
+    pub fn with_captures<T, F>(&self, f: F) -> Option<T>
+    where
+        F: FnOnce(&Regex) -> T,
+    {
+        let mut captures = self.0.captures.borrow_mut();
+        let regex = captures.get_or_insert_with(|| {
+            let pattern = format!("^(?:{})$", self.pattern());
+            Regex::new(&pattern)
+                .map_err(|err| error!("Unable to extract hint capture groups: {}", err))
+                .ok()
+        });
+
+        regex.as_ref().map(f)
+    }
+
+    /// Get the uncompiled regex pattern.
+    pub fn pattern(&self) -> String {
+        self.0.variant.borrow().pattern().to_owned()
+    }
+
+    /// Start compiling the regex DFAs on a separate thread.
+    ///
+    /// The `notify` callback is run on the compiling thread once the result is available.
+    pub fn compile_in_background<F>(&self, size_limit: usize, notify: F)
+    where
+        F: Fn() + Send + 'static,
+    {
+        let mut variant = self.0.variant.borrow_mut();
+        if let LazyRegexVariant::Pattern(regex) = &*variant {
+            let job = CompileJob::spawn(regex.clone(), size_limit, notify);
+            *variant = LazyRegexVariant::Compiling(regex.clone(), job);
+        }
     }
 }
 
@@ -372,53 +503,134 @@ impl<'de> Deserialize<'de> for LazyRegex {
         D: Deserializer<'de>,
     {
         let regex = LazyRegexVariant::Pattern(String::deserialize(deserializer)?);
-        Ok(Self(Rc::new(RefCell::new(regex))))
+        Ok(Self(Rc::new(LazyRegexState::new(regex))))
     }
 }
 
-/// Regex which is compiled on demand, to avoid expensive computations at startup.
-#[derive(Clone, Debug)]
+impl From<&str> for LazyRegex {
+    fn from(regex: &str) -> Self {
+        let regex = LazyRegexVariant::Pattern(regex.to_owned());
+        Self(Rc::new(LazyRegexState::new(regex)))
+    }
+}
+
+impl PartialEq for LazyRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.variant == other.0.variant
+    }
+}
+impl Eq for LazyRegex {}
+
+/// Compiled variants of a lazy regex.
+#[derive(Debug)]
+struct LazyRegexState {
+    variant: RefCell<LazyRegexVariant>,
+
+    /// Anchored regex for capture group extraction, `None` until first use.
+    captures: RefCell<Option<Option<Regex>>>,
+}
+
+impl LazyRegexState {
+    fn new(variant: LazyRegexVariant) -> Self {
+        Self { variant: RefCell::new(variant), captures: Default::default() }
+    }
+}
+
+/// Regex which is compiled in the background, to avoid expensive computations at startup.
+#[derive(Debug)]
 pub enum LazyRegexVariant {
-    Compiled(Box<RegexSearch>),
+    Compiled(String, Box<RegexSearch>),
+    Compiling(String, CompileJob),
     Pattern(String),
 }
 
 impl LazyRegexVariant {
     /// Get a reference to the compiled regex.
     ///
-    /// If the regex is not already compiled, this will compile the DFAs and store them for future
-    /// access.
-    fn compiled(&mut self) -> &RegexSearch {
-        // Check if the regex has already been compiled.
-        let regex = match self {
-            Self::Compiled(regex_search) => return regex_search,
-            Self::Pattern(regex) => regex,
+    /// Returns `None` until the background compilation has finished. Compilation is never started
+    /// from here, since only the configuration knows the size limit and how to request a redraw.
+    fn compiled(&mut self) -> Option<&RegexSearch> {
+        let result = match self {
+            Self::Compiled(_, regex_search) => return Some(regex_search),
+            Self::Compiling(_, job) => match job.receiver.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => Err(RegexError::Cancelled),
+            },
+            Self::Pattern(_) => return None,
         };
 
-        // Compile the regex.
-        let regex_search = match RegexSearch::new(&regex) {
+        let regex_search = match result {
             Ok(regex_search) => regex_search,
             Err(error) => {
                 error!("hint regex is invalid: {}", error);
                 RegexSearch::new("").unwrap()
             },
         };
-        *self = Self::Compiled(Box::new(regex_search));
+        let regex = mem::take(self.pattern_mut());
+        *self = Self::Compiled(regex, Box::new(regex_search));
 
         // Return a reference to the compiled DFAs.
         match self {
-            Self::Compiled(dfas) => dfas,
-            Self::Pattern(_) => unreachable!(),
+            Self::Compiled(_, dfas) => Some(dfas),
+            _ => unreachable!(),
         }
     }
+
+    /// Get the uncompiled regex pattern.
+    fn pattern(&self) -> &str {
+        match self {
+            Self::Compiled(regex, _) | Self::Compiling(regex, _) | Self::Pattern(regex) => regex,
+        }
+    }
+
+    /// Get mutable access to the uncompiled regex pattern.
+    fn pattern_mut(&mut self) -> &mut String {
+        match self {
+            Self::Compiled(regex, _) | Self::Compiling(regex, _) | Self::Pattern(regex) => regex,
+        }
+    }
+}
+
+/// Regex compilation running on a separate thread.
+///
+/// Dropping the job cancels the compilation.
+#[derive(Debug)]
+pub struct CompileJob {
+    receiver: Receiver<Result<RegexSearch, RegexError>>,
+    cancel: CancelHandle,
+}
+
+impl CompileJob {
+    fn spawn<F>(regex: String, size_limit: usize, notify: F) -> Self
+    where
+        F: Fn() + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = CancelHandle::default();
+
+        let thread_cancel = cancel.clone();
+        thread::spawn_named("hint regex compiler", move || {
+            let flags = Default::default();
+            let result = RegexSearch::cancellable(&regex, flags, size_limit, &thread_cancel);
+            if !thread_cancel.is_cancelled() && sender.send(result).is_ok() {
+                notify();
+            }
+        });
+
+        Self { receiver, cancel }
+    }
+}
+
+impl Drop for CompileJob {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
 }
 
 impl PartialEq for LazyRegexVariant {
     fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Self::Pattern(regex), Self::Pattern(other_regex)) => regex == other_regex,
-            _ => false,
-        }
+        self.pattern() == other.pattern()
     }
 }
 impl Eq for LazyRegexVariant {}
@@ -0,0 +1,469 @@
+//! Serialization of terminal text with its colors and attributes.
+
+use std::fmt::Write;
+use std::mem;
+
+use alacritty_terminal::ansi::{Color, NamedColor};
+use alacritty_terminal::config::SelectionCopy;
+use alacritty_terminal::term::cell::{Cell, Flags};
+use alacritty_terminal::term::color::Rgb;
+use alacritty_terminal::term::TextSink;
+
+use crate::display::color::{List, DIM_FACTOR};
+
+/// Cell flags which affect the appearance of text.
+const STYLE_FLAGS: Flags = Flags::from_bits_truncate(
+    Flags::BOLD.bits()
+        | Flags::DIM.bits()
+        | Flags::ITALIC.bits()
+        | Flags::UNDERLINE.bits()
+        | Flags::DOUBLE_UNDERLINE.bits()
+        | Flags::INVERSE.bits()
+        | Flags::HIDDEN.bits()
+        | Flags::STRIKEOUT.bits(),
+);
+
+/// Format used for copying styled text.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CopyFormat {
+    /// Text with ANSI escape sequences.
+    Ansi,
+
+    /// HTML with inline styles.
+    Html,
+
+    /// Rich text format.
+    Rtf,
+}
+
+impl CopyFormat {
+    /// MIME type offered to the clipboard.
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            CopyFormat::Ansi => "text/plain;charset=utf-8",
+            CopyFormat::Html => "text/html",
+            CopyFormat::Rtf => "text/rtf",
+        }
+    }
+}
+
+/// Visual style of a text segment.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Style {
+    fg: Color,
+    bg: Color,
+    flags: Flags,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            fg: Color::Named(NamedColor::Foreground),
+            bg: Color::Named(NamedColor::Background),
+            flags: Flags::empty(),
+        }
+    }
+}
+
+impl Style {
+    /// Resolve foreground and background to RGB using the active palette.
+    fn rgb(&self, colors: &List, bold_bright: bool) -> (Rgb, Rgb) {
+        let dim = self.flags.contains(Flags::DIM);
+        let bright = bold_bright && !dim && self.flags.contains(Flags::BOLD);
+
+        let mut fg = match self.fg {
+            Color::Spec(rgb) if dim => rgb * DIM_FACTOR,
+            Color::Spec(rgb) => rgb,
+            Color::Named(named) if bright => colors[named.to_bright()],
+            Color::Named(named) if dim => colors[named.to_dim()],
+            Color::Named(named) => colors[named],
+            Color::Indexed(index @ 0..=7) if bright => colors[index as usize + 8],
+            Color::Indexed(index) => colors[index as usize],
+        };
+        let mut bg = match self.bg {
+            Color::Spec(rgb) => rgb,
+            Color::Named(named) => colors[named],
+            Color::Indexed(index) => colors[index as usize],
+        };
+
+        if self.flags.contains(Flags::INVERSE) {
+            std::mem::swap(&mut fg, &mut bg);
+        }
+
+        if self.flags.contains(Flags::HIDDEN) {
+            fg = bg;
+        }
+
+        (fg, bg)
+    }
+}
+
+/// Consecutive characters sharing the same style.
+#[derive(Debug)]
+struct Segment {
+    /// Style of the text, `None` for line breaks.
+    style: Option<Style>,
+    text: String,
+}
+
+/// Text with the style of every character.
+#[derive(Debug, Default)]
+pub struct StyledText {
+    segments: Vec<Segment>,
+}
+
+impl TextSink for StyledText {
+    fn push_cell(&mut self, c: char, cell: Option<&Cell>) {
+        let style =
+            cell.map(|cell| Style { fg: cell.fg, bg: cell.bg, flags: cell.flags & STYLE_FLAGS });
+        self.push_styled(c, style);
+    }
+}
+
+impl StyledText {
+    /// Apply the post-processing of copied text, like `Term::process_copied_text` does for plain
+    /// text.
+    pub fn process(&mut self, copy: SelectionCopy) {
+        if !copy.trim_trailing_whitespace && !copy.collapse_blank_lines {
+            return;
+        }
+
+        // Split the text into lines of styled characters.
+        let mut lines = Vec::new();
+        let mut line = Vec::new();
+        for segment in self.segments.drain(..) {
+            for c in segment.text.chars() {
+                if c == '\n' {
+                    lines.push(mem::take(&mut line));
+                } else {
+                    line.push((c, segment.style));
+                }
+            }
+        }
+        lines.push(line);
+
+        let last = lines.len() - 1;
+        let mut previous_blank = false;
+        for (i, mut line) in lines.into_iter().enumerate() {
+            let blank = line.iter().all(|(c, _)| c.is_whitespace());
+            if copy.collapse_blank_lines && blank && previous_blank && i != last {
+                continue;
+            }
+            previous_blank = blank;
+
+            if copy.trim_trailing_whitespace {
+                while matches!(line.last(), Some((c, _)) if c.is_whitespace()) {
+                    line.pop();
+                }
+            }
+
+            for (c, style) in line {
+                self.push_styled(c, style);
+            }
+
+            if i != last {
+                self.push_styled('\n', None);
+            }
+        }
+    }
+
+    /// Add a character, extending the last segment if it has the same style.
+    fn push_styled(&mut self, c: char, style: Option<Style>) {
+        match self.segments.last_mut() {
+            Some(segment) if segment.style == style => segment.text.push(c),
+            _ => self.segments.push(Segment { style, text: c.to_string() }),
+        }
+    }
+
+    /// Serialize the text in the requested format.
+    pub fn format(&self, format: CopyFormat, colors: &List, bold_bright: bool) -> String {
+        match format {
+            CopyFormat::Ansi => self.to_ansi(),
+            CopyFormat::Html => self.to_html(colors, bold_bright),
+            CopyFormat::Rtf => self.to_rtf(colors, bold_bright),
+        }
+    }
+
+    /// Serialize the text with SGR escape sequences.
+    pub fn to_ansi(&self) -> String {
+        let mut ansi = String::new();
+        let mut current = Style::default();
+
+        for segment in &self.segments {
+            let style = segment.style.unwrap_or_default();
+            if style != current {
+                ansi.push_str("\x1b[0");
+                push_sgr_flags(&mut ansi, style.flags);
+                push_sgr_color(&mut ansi, style.fg, 30);
+                push_sgr_color(&mut ansi, style.bg, 40);
+                ansi.push('m');
+                current = style;
+            }
+
+            ansi.push_str(&segment.text);
+        }
+
+        if current != Style::default() {
+            ansi.push_str("\x1b[0m");
+        }
+
+        ansi
+    }
+
+    /// Serialize the text as preformatted HTML with inline styles.
+    fn to_html(&self, colors: &List, bold_bright: bool) -> String {
+        let default_fg = colors[NamedColor::Foreground];
+        let default_bg = colors[NamedColor::Background];
+
+        let mut html = format!(
+            "<pre style=\"color:{};background-color:{};font-family:monospace\">",
+            default_fg, default_bg
+        );
+
+        for segment in &self.segments {
+            let style = match segment.style {
+                Some(style) if style != Style::default() => style,
+                _ => {
+                    push_html_escaped(&mut html, &segment.text);
+                    continue;
+                },
+            };
+
+            let (fg, bg) = style.rgb(colors, bold_bright);
+
+            let mut css = String::new();
+            if fg != default_fg {
+                let _ = write!(css, "color:{};", fg);
+            }
+            if bg != default_bg {
+                let _ = write!(css, "background-color:{};", bg);
+            }
+            if style.flags.contains(Flags::BOLD) {
+                css.push_str("font-weight:bold;");
+            }
+            if style.flags.contains(Flags::ITALIC) {
+                css.push_str("font-style:italic;");
+            }
+
+            let underline = style.flags.intersects(Flags::UNDERLINE | Flags::DOUBLE_UNDERLINE);
+            match (underline, style.flags.contains(Flags::STRIKEOUT)) {
+                (true, true) => css.push_str("text-decoration:underline line-through;"),
+                (true, false) => css.push_str("text-decoration:underline;"),
+                (false, true) => css.push_str("text-decoration:line-through;"),
+                (false, false) => (),
+            }
+            if style.flags.contains(Flags::DOUBLE_UNDERLINE) {
+                css.push_str("text-decoration-style:double;");
+            }
+
+            if css.is_empty() {
+                push_html_escaped(&mut html, &segment.text);
+            } else {
+                let _ = write!(html, "<span style=\"{}\">", css.trim_end_matches(';'));
+                push_html_escaped(&mut html, &segment.text);
+                html.push_str("</span>");
+            }
+        }
+
+        html.push_str("</pre>");
+
+        html
+    }
+
+    /// Serialize the text as an RTF document.
+    fn to_rtf(&self, colors: &List, bold_bright: bool) -> String {
+        let default_style = Style::default();
+        let mut color_table = vec![];
+        let mut body = String::new();
+
+        for segment in &self.segments {
+            let style = segment.style.unwrap_or(default_style);
+            let (fg, bg) = style.rgb(colors, bold_bright);
+
+            let fg_index = rtf_color_index(&mut color_table, fg);
+            let bg_index = rtf_color_index(&mut color_table, bg);
+            let _ = write!(body, "{{\\cf{}\\chcbpat{}", fg_index, bg_index);
+
+            if style.flags.contains(Flags::BOLD) {
+                body.push_str("\\b");
+            }
+            if style.flags.contains(Flags::ITALIC) {
+                body.push_str("\\i");
+            }
+            if style.flags.contains(Flags::DOUBLE_UNDERLINE) {
+                body.push_str("\\uldb");
+            } else if style.flags.contains(Flags::UNDERLINE) {
+                body.push_str("\\ul");
+            }
+            if style.flags.contains(Flags::STRIKEOUT) {
+                body.push_str("\\strike");
+            }
+
+            body.push(' ');
+            push_rtf_escaped(&mut body, &segment.text);
+            body.push('}');
+        }
+
+        let mut rtf = String::from("{\\rtf1\\ansi\\deff0{\\fonttbl{\\f0\\fmodern monospace;}}");
+        rtf.push_str("{\\colortbl;");
+        for color in color_table {
+            let _ = write!(rtf, "\\red{}\\green{}\\blue{};", color.r, color.g, color.b);
+        }
+        rtf.push_str("}\\f0\n");
+        rtf.push_str(&body);
+        rtf.push('}');
+
+        rtf
+    }
+}
+
+/// Append the SGR parameters for the style flags.
+fn push_sgr_flags(ansi: &mut String, flags: Flags) {
+    let params = [
+        (Flags::BOLD, ";1"),
+        (Flags::DIM, ";2"),
+        (Flags::ITALIC, ";3"),
+        (Flags::UNDERLINE, ";4"),
+        (Flags::INVERSE, ";7"),
+        (Flags::HIDDEN, ";8"),
+        (Flags::STRIKEOUT, ";9"),
+        (Flags::DOUBLE_UNDERLINE, ";21"),
+    ];
+
+    for (flag, param) in params.iter() {
+        if flags.contains(*flag) {
+            ansi.push_str(param);
+        }
+    }
+}
+
+/// Append the SGR parameters for a color, using `base` for the first normal color.
+fn push_sgr_color(ansi: &mut String, color: Color, base: u8) {
+    let _ = match color {
+        Color::Named(named) if (named as usize) < 8 => write!(ansi, ";{}", base + named as u8),
+        Color::Named(named) if (named as usize) < 16 => {
+            write!(ansi, ";{}", base + 60 + named as u8 - 8)
+        },
+        // Default and dim colors are expressed through the reset and the DIM flag.
+        Color::Named(_) => Ok(()),
+        Color::Indexed(index) => write!(ansi, ";{};5;{}", base + 8, index),
+        Color::Spec(rgb) => write!(ansi, ";{};2;{};{};{}", base + 8, rgb.r, rgb.g, rgb.b),
+    };
+}
+
+/// Append text with HTML special characters escaped.
+fn push_html_escaped(html: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            c => html.push(c),
+        }
+    }
+}
+
+/// Append text with RTF control characters and non-ASCII characters escaped.
+fn push_rtf_escaped(rtf: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '\\' | '{' | '}' => {
+                rtf.push('\\');
+                rtf.push(c);
+            },
+            '\n' => rtf.push_str("\\line\n"),
+            '\t' => rtf.push_str("\\tab "),
+            c if c.is_ascii() => rtf.push(c),
+            c => {
+                // RTF expects signed 16 bit UTF-16 code units, followed by an ASCII fallback.
+                let mut buf = [0; 2];
+                for unit in c.encode_utf16(&mut buf) {
+                    let _ = write!(rtf, "\\u{}?", *unit as i16);
+                }
+            },
+        }
+    }
+}
+
+/// Index of a color in the RTF color table, adding it if necessary.
+fn rtf_color_index(color_table: &mut Vec<Rgb>, color: Rgb) -> usize {
+    // Index zero is reserved for the automatic color.
+    match color_table.iter().position(|c| *c == color) {
+        Some(index) => index + 1,
+        None => {
+            color_table.push(color);
+            color_table.len()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::color::Colors;
+
+    fn styled_text() -> StyledText {
+        let mut text = StyledText::default();
+
+        let mut cell = Cell::default();
+        cell.fg = Color::Named(NamedColor::Red);
+        cell.flags = Flags::BOLD;
+        for c in "a<b".chars() {
+            text.push_cell(c, Some(&cell));
+        }
+        text.push_cell('\n', None);
+
+        let cell = Cell::default();
+        text.push_cell('ü', Some(&cell));
+
+        text
+    }
+
+    #[test]
+    fn copy_ansi() {
+        let ansi = styled_text().to_ansi();
+        assert_eq!(ansi, "\x1b[0;1;31ma<b\x1b[0m\nü");
+    }
+
+    #[test]
+    fn process_styled_text() {
+        let mut text = StyledText::default();
+        let mut cell = Cell::default();
+        cell.fg = Color::Named(NamedColor::Red);
+        for c in "a \n\n \n\nb ".chars() {
+            text.push_cell(c, if c == '\n' { None } else { Some(&cell) });
+        }
+
+        let copy = SelectionCopy {
+            trim_trailing_whitespace: true,
+            collapse_blank_lines: true,
+            ..SelectionCopy::default()
+        };
+        text.process(copy);
+        assert_eq!(text.to_ansi(), "\x1b[0;31ma\x1b[0m\n\n\x1b[0;31mb\x1b[0m");
+    }
+
+    #[test]
+    fn copy_html() {
+        let colors = List::from(&Colors::default());
+        let html = styled_text().to_html(&colors, false);
+
+        let red = colors[NamedColor::Red];
+        let expected =
+            format!("<span style=\"color:{};font-weight:bold\">a&lt;b</span>\nü</pre>", red);
+        assert!(html.starts_with("<pre style="));
+        assert!(html.ends_with(&expected));
+    }
+
+    #[test]
+    fn copy_rtf() {
+        let colors = List::from(&Colors::default());
+        let rtf = styled_text().to_rtf(&colors, false);
+
+        assert!(rtf.starts_with("{\\rtf1"));
+        assert!(rtf.contains("{\\cf1\\chcbpat2\\b a<b}"));
+        assert!(rtf.ends_with("{\\cf3\\chcbpat2 \\u252?}}"));
+    }
+}
@@ -1,14 +1,18 @@
 use std::ffi::OsStr;
 use std::fmt::Debug;
-use std::io;
+use std::io::{self, Read};
 #[cfg(not(windows))]
 use std::os::unix::process::CommandExt;
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
-use std::process::{Command, Stdio};
+use std::process::{Command, Output, Stdio};
+use std::sync::mpsc::{self, Sender};
+use std::time::{Duration, Instant};
 
 use log::{debug, warn};
 
+use alacritty_terminal::thread;
+
 #[cfg(windows)]
 use winapi::um::winbase::{CREATE_NEW_PROCESS_GROUP, CREATE_NO_WINDOW};
 
@@ -24,6 +28,84 @@ where
     }
 }
 
+/// Run a program to completion, capturing its output.
+///
+/// The program is killed if it does not exit before the `timeout`. Only the first `max_bytes` of
+/// stdout and stderr are captured, the rest of the output is discarded.
+pub fn run_with_output<I, S>(
+    program: &str,
+    args: I,
+    timeout: Duration,
+    max_bytes: usize,
+) -> io::Result<Output>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut command = Command::new(program);
+    command.args(args).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    // Prevent console applications from opening a new console window.
+    #[cfg(windows)]
+    command.creation_flags(CREATE_NO_WINDOW);
+
+    let mut child = command.spawn()?;
+    let deadline = Instant::now() + timeout;
+
+    // Read output on separate threads, since processes block once the pipes are full.
+    let (sender, receiver) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        read_capped("command stdout", stdout, max_bytes, sender.clone(), true);
+    }
+    if let Some(stderr) = child.stderr.take() {
+        read_capped("command stderr", stderr, max_bytes, sender, false);
+    }
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            let message = format!("did not exit within {} seconds", timeout.as_secs());
+            return Err(io::Error::new(io::ErrorKind::TimedOut, message));
+        }
+
+        std::thread::sleep(Duration::from_millis(10));
+    };
+
+    // Children of the program might keep the pipes open, so output is not awaited indefinitely.
+    let mut output = Output { status, stdout: Vec::new(), stderr: Vec::new() };
+    for _ in 0..2 {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(timeout) {
+            Ok((true, bytes)) => output.stdout = bytes,
+            Ok((false, bytes)) => output.stderr = bytes,
+            Err(_) => break,
+        }
+    }
+
+    Ok(output)
+}
+
+/// Read up to `max_bytes` from a pipe on a new thread, discarding the remaining data.
+fn read_capped<R: Read + Send + 'static>(
+    name: &str,
+    mut reader: R,
+    max_bytes: usize,
+    sender: Sender<(bool, Vec<u8>)>,
+    is_stdout: bool,
+) {
+    thread::spawn_named(name, move || {
+        let mut bytes = Vec::new();
+        let _ = reader.by_ref().take(max_bytes as u64).read_to_end(&mut bytes);
+        let _ = sender.send((is_stdout, bytes));
+        let _ = io::copy(&mut reader, &mut io::sink());
+    });
+}
+
 #[cfg(windows)]
 fn spawn_daemon<I, S>(program: &str, args: I) -> io::Result<()>
 where
@@ -74,3 +156,28 @@ where
             .map(|_| ())
     }
 }
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_is_capped() {
+        let args = ["-c", "head -c 100000 /dev/zero; echo error >&2"];
+        let output = run_with_output("sh", args.iter(), Duration::from_secs(10), 10).unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(output.stdout, vec![0; 10]);
+        assert_eq!(output.stderr, b"error\n");
+    }
+
+    #[test]
+    fn slow_program_is_killed() {
+        let start = Instant::now();
+        let err =
+            run_with_output("sleep", ["10"].iter(), Duration::from_millis(100), 10).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
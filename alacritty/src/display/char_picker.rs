@@ -0,0 +1,58 @@
+//! Unicode characters listed in the character picker.
+
+use unicode_width::UnicodeWidthChar;
+
+include!(concat!(env!("OUT_DIR"), "/unicode_names.rs"));
+
+/// Character at an index of the Unicode name table.
+pub fn character(index: usize) -> char {
+    UNICODE_NAMES[index].0
+}
+
+/// Text describing a character in the picker, with its code point and name.
+pub fn label(index: usize) -> String {
+    let (c, name, _) = UNICODE_NAMES[index];
+
+    // Pad narrow characters to keep the code points aligned.
+    let spacer = if c.width() == Some(2) { "" } else { " " };
+    format!("{}{} U+{:04X}  {}", c, spacer, c as u32, name)
+}
+
+/// Indices of all characters whose name or keywords contain every word of the query.
+///
+/// Shorter names are listed first, since they are usually the closest match.
+pub fn search(query: &str) -> Vec<usize> {
+    let query = query.to_lowercase();
+    let words: Vec<&str> = query.split_whitespace().collect();
+
+    let mut matches: Vec<usize> = (0..UNICODE_NAMES.len())
+        .filter(|&index| {
+            let (_, name, keywords) = UNICODE_NAMES[index];
+            words.iter().all(|word| name.contains(word) || keywords.contains(word))
+        })
+        .collect();
+    matches.sort_by_key(|&index| UNICODE_NAMES[index].1.len());
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_by_name_and_keywords() {
+        assert_eq!(search("").len(), UNICODE_NAMES.len());
+        assert_eq!(search("rightwards arrow").first().map(|&index| character(index)), Some('→'));
+
+        let matches: Vec<_> = search("Deploy").into_iter().map(label).collect();
+        assert_eq!(matches, vec!["🚀 U+1F680  rocket"]);
+
+        assert!(search("deployx").is_empty());
+    }
+
+    #[test]
+    fn words_match_in_any_order() {
+        assert_eq!(search("arrow left").first().map(|&index| character(index)), Some('←'));
+    }
+}
@@ -0,0 +1,57 @@
+//! Clipboard history entries listed in the clipboard picker.
+
+/// Text describing a clipboard history entry in the picker.
+///
+/// Surrounding whitespace is removed, line breaks are shown as `↵` and other control characters
+/// are dropped.
+pub fn label(entry: &str) -> String {
+    entry
+        .trim()
+        .chars()
+        .map(|c| if c == '\n' { '↵' } else { c })
+        .filter(|c| !c.is_control())
+        .collect()
+}
+
+/// Indices of all entries matching the query.
+pub fn search(entries: &[String], query: &str) -> Vec<usize> {
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| fuzzy_match(query, entry))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Check if all characters of the query appear in the text, in order.
+///
+/// Matching is case insensitive unless the query contains uppercase characters.
+fn fuzzy_match(query: &str, text: &str) -> bool {
+    let case_sensitive = query.chars().any(char::is_uppercase);
+
+    let mut text_chars = text.chars();
+    query.chars().all(|query_char| {
+        text_chars.any(|text_char| {
+            if case_sensitive {
+                text_char == query_char
+            } else {
+                text_char.to_lowercase().eq(query_char.to_lowercase())
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_matching() {
+        assert!(fuzzy_match("", "anything"));
+        assert!(fuzzy_match("gco", "git checkout"));
+        assert!(fuzzy_match("GIT", "GIT checkout"));
+        assert!(fuzzy_match("git", "GIT checkout"));
+        assert!(!fuzzy_match("GIT", "git checkout"));
+        assert!(!fuzzy_match("ocg", "git checkout"));
+    }
+}
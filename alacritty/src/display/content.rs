@@ -30,6 +30,7 @@ pub struct RenderableContent<'a> {
     cursor: Option<RenderableCursor>,
     cursor_shape: CursorShape,
     cursor_point: Point<usize>,
+    mark_mode: bool,
     search: Option<Regex<'a>>,
     hint: Option<Hint<'a>>,
     config: &'a Config<UiConfig>,
@@ -43,13 +44,16 @@ impl<'a> RenderableContent<'a> {
         display: &'a mut Display,
         term: &'a Term<T>,
         search_state: &'a SearchState,
+        mark_point: Option<Point>,
     ) -> Self {
         let search = search_state.dfas().map(|dfas| Regex::new(&term, dfas));
         let focused_match = search_state.focused_match();
         let terminal_content = term.renderable_content();
 
         // Find terminal cursor shape.
-        let cursor_shape = if terminal_content.cursor.shape == CursorShape::Hidden
+        let cursor_shape = if mark_point.is_some() {
+            CursorShape::Block
+        } else if terminal_content.cursor.shape == CursorShape::Hidden
             || display.cursor_hidden
             || search_state.regex().is_some()
         {
@@ -60,10 +64,14 @@ impl<'a> RenderableContent<'a> {
             terminal_content.cursor.shape
         };
 
-        // Convert terminal cursor point to viewport position.
-        let cursor_point = terminal_content.cursor.point;
+        // Convert terminal cursor point to viewport position, replacing it with the mark cursor.
+        let cursor_point = mark_point.unwrap_or(terminal_content.cursor.point);
         let display_offset = terminal_content.display_offset;
-        let cursor_point = display::point_to_viewport(display_offset, cursor_point).unwrap();
+        let cursor_point = display::point_to_viewport(display_offset, cursor_point);
+
+        // Hide the mark cursor while it is scrolled out of the viewport.
+        let cursor_shape = if cursor_point.is_some() { cursor_shape } else { CursorShape::Hidden };
+        let cursor_point = cursor_point.unwrap_or_default();
 
         let hint = if display.hint_state.active() {
             display.hint_state.update_matches(term);
@@ -79,6 +87,7 @@ impl<'a> RenderableContent<'a> {
             focused_match,
             cursor_shape,
             cursor_point,
+            mark_mode: mark_point.is_some(),
             search,
             config,
             hint,
@@ -112,7 +121,7 @@ impl<'a> RenderableContent<'a> {
         }
 
         // Cursor colors.
-        let color = if self.terminal_content.mode.contains(TermMode::VI) {
+        let color = if self.mark_mode || self.terminal_content.mode.contains(TermMode::VI) {
             self.config.ui_config.colors.vi_mode_cursor
         } else {
             self.config.ui_config.colors.cursor
@@ -230,6 +239,10 @@ impl RenderableCell {
             Self::compute_cell_rgb(&mut fg, &mut bg, &mut bg_alpha, config_fg, config_bg);
 
             character = c;
+        } else if matches!(&content.hint, Some(hint) if hint.is_selected(cell.point)) {
+            let config_fg = colors.hints.selected.foreground;
+            let config_bg = colors.hints.selected.background;
+            Self::compute_cell_rgb(&mut fg, &mut bg, &mut bg_alpha, config_fg, config_bg);
         } else if is_selected {
             let config_fg = colors.selection.foreground;
             let config_bg = colors.selection.background;
@@ -392,8 +405,11 @@ struct Hint<'a> {
     /// Hint matches and position.
     regex: Regex<'a>,
 
-    /// Last match checked against current cell position.
-    labels: &'a Vec<Vec<char>>,
+    /// Hint state providing the key labels of visible matches.
+    hint_state: &'a HintState,
+
+    /// Matches toggled in multi-select mode.
+    selected: &'a [Match],
 }
 
 impl<'a> Hint<'a> {
@@ -422,14 +438,20 @@ impl<'a> Hint<'a> {
         let is_first = label_position == 0;
 
         // Hint label character.
-        self.labels[self.regex.index].get(label_position).copied().map(|c| (c, is_first))
+        let label = self.hint_state.label(self.regex.index)?;
+        label.get(label_position).copied().map(|c| (c, is_first))
+    }
+
+    /// Check if the point is part of a match toggled in multi-select mode.
+    fn is_selected(&self, point: Point) -> bool {
+        self.selected.iter().any(|bounds| bounds.contains(&point))
     }
 }
 
 impl<'a> From<&'a HintState> for Hint<'a> {
     fn from(hint_state: &'a HintState) -> Self {
         let regex = Regex { matches: Cow::Borrowed(hint_state.matches()), index: 0 };
-        Self { labels: hint_state.labels(), regex }
+        Self { hint_state, selected: hint_state.selected(), regex }
     }
 }
 
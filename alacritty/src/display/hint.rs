@@ -1,13 +1,16 @@
-use std::cmp::{max, min};
+use std::cmp::{max, min, Ordering};
+use std::io;
+use std::mem;
+use std::process::Output;
 
 use glutin::event::ModifiersState;
 
-use alacritty_terminal::grid::BidirectionalIterator;
-use alacritty_terminal::index::{Boundary, Direction, Point};
+use alacritty_terminal::grid::{BidirectionalIterator, Dimensions};
+use alacritty_terminal::index::{Boundary, Column, Direction, Line, Point};
 use alacritty_terminal::term::search::{Match, RegexIter, RegexSearch};
 use alacritty_terminal::term::{Term, TermMode};
 
-use crate::config::ui_config::{Hint, HintAction};
+use crate::config::ui_config::{Hint, LazyRegex};
 use crate::config::Config;
 use crate::display::content::RegexMatches;
 use crate::display::MAX_SEARCH_LINES;
@@ -24,13 +27,27 @@ pub struct HintState {
     alphabet: String,
 
     /// Visible matches.
+    ///
+    /// For hints covering the scrollback, this contains all matches in the terminal history.
     matches: RegexMatches,
 
-    /// Key label for each visible match.
+    /// Key labels for all matches inside the viewport.
     labels: Vec<Vec<char>>,
 
+    /// Index of the match with the first key label.
+    label_offset: usize,
+
     /// Keys pressed for hint selection.
     keys: Vec<char>,
+
+    /// Matches toggled in multi-select mode.
+    selected: Vec<Match>,
+
+    /// Scrollback matches need to be recomputed.
+    dirty: bool,
+
+    /// Terminal state during the last search of the scrollback history.
+    scrollback: Option<ScrollbackState>,
 }
 
 impl HintState {
@@ -41,7 +58,11 @@ impl HintState {
             hint: Default::default(),
             matches: Default::default(),
             labels: Default::default(),
+            label_offset: Default::default(),
             keys: Default::default(),
+            selected: Default::default(),
+            dirty: Default::default(),
+            scrollback: Default::default(),
         }
     }
 
@@ -53,6 +74,7 @@ impl HintState {
     /// Start the hint selection process.
     pub fn start(&mut self, hint: Hint) {
         self.hint = Some(hint);
+        self.dirty = true;
     }
 
     /// Cancel the hint highlighting process.
@@ -60,31 +82,59 @@ impl HintState {
         self.matches.clear();
         self.labels.clear();
         self.keys.clear();
+        self.selected.clear();
+        self.scrollback = None;
         self.hint = None;
     }
 
+    /// Mark the hint matches as outdated after the terminal content has changed.
+    pub fn invalidate_matches(&mut self) {
+        self.dirty = true;
+    }
+
     /// Update the visible hint matches and key labels.
     pub fn update_matches<T>(&mut self, term: &Term<T>) {
-        let hint = match self.hint.as_mut() {
+        let hint = match self.hint.as_ref() {
             Some(hint) => hint,
             None => return,
         };
 
-        // Find visible matches.
-        self.matches.0 = hint.regex.with_compiled(|regex| {
-            let mut matches = RegexMatches::new(term, regex);
-
-            // Apply post-processing and search for sub-matches if necessary.
-            if hint.post_processing {
-                matches
-                    .drain(..)
-                    .map(|rm| HintPostProcessor::new(term, regex, rm).collect::<Vec<_>>())
-                    .flatten()
-                    .collect()
-            } else {
-                matches.0
+        // Find visible matches, or all matches in the scrollback history.
+        if !hint.scrollback {
+            let matches = hint.regex.with_compiled(|regex| {
+                let matches = RegexMatches::new(term, regex);
+                post_process(hint, term, regex, matches.0)
+            });
+
+            // Keep the matches outdated until the regex is compiled.
+            self.dirty = matches.is_none();
+            self.matches.0 = matches.unwrap_or_default();
+        } else if self.dirty {
+            let state = ScrollbackState::new(term);
+            let scrolled = self.scrollback.and_then(|old| state.scrolled_since(&old));
+
+            let matches = &mut self.matches.0;
+            let searched = hint.regex.with_compiled(|regex| {
+                // Only search lines which might have changed since the last search.
+                let start = match scrolled {
+                    Some(scrolled) => shift_matches(matches, term, scrolled),
+                    None => {
+                        matches.clear();
+                        Point::new(term.topmost_line(), Column(0))
+                    },
+                };
+                let end = Point::new(term.bottommost_line(), term.last_column());
+
+                let new_matches = RegexIter::new(start, end, Direction::Right, term, regex);
+                matches.append(&mut post_process(hint, term, regex, new_matches.collect()));
+            });
+
+            // Keep the matches outdated until the regex is compiled.
+            self.dirty = searched.is_none();
+            if searched.is_some() {
+                self.scrollback = Some(state);
             }
-        });
+        }
 
         // Cancel highlight with no visible matches.
         if self.matches.is_empty() {
@@ -92,24 +142,51 @@ impl HintState {
             return;
         }
 
-        let mut generator = HintLabels::new(&self.alphabet, HINT_SPLIT_PERCENTAGE);
+        let viewport_start = Line(-(term.grid().display_offset() as i32));
+        let viewport_end = viewport_start + term.bottommost_line();
+
+        // Find the matches inside the viewport.
+        let start = self
+            .matches
+            .binary_search_by(|rm| {
+                if rm.end().line < viewport_start {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            })
+            .unwrap_or_else(|index| index);
+        let end = self
+            .matches
+            .binary_search_by(|rm| {
+                if rm.start().line <= viewport_end {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            })
+            .unwrap_or_else(|index| index);
+
+        let generator = HintLabels::new(&self.alphabet, HINT_SPLIT_PERCENTAGE);
         let match_count = self.matches.len();
         let keys_len = self.keys.len();
 
-        // Get the label for each match.
-        self.labels.resize(match_count, Vec::new());
-        for i in (0..match_count).rev() {
-            let mut label = generator.next();
+        // Only generate labels for visible matches, starting with the shortest label at the
+        // bottom of the history.
+        self.labels.clear();
+        self.label_offset = start;
+        for i in start..end {
+            let mut label = generator.label(match_count - 1 - i);
             if label.len() >= keys_len && label[..keys_len] == self.keys[..] {
-                self.labels[i] = label.split_off(keys_len);
+                self.labels.push(label.split_off(keys_len));
             } else {
-                self.labels[i] = Vec::new();
+                self.labels.push(Vec::new());
             }
         }
     }
 
     /// Handle keyboard input during hint selection.
-    pub fn keyboard_input<T>(&mut self, term: &Term<T>, c: char) -> Option<HintMatch> {
+    pub fn keyboard_input<T>(&mut self, term: &Term<T>, c: char) -> Option<HintSelection> {
         match c {
             // Use backspace to remove the last character pressed.
             '\x08' | '\x1f' => {
@@ -117,6 +194,10 @@ impl HintState {
             },
             // Cancel hint highlighting on ESC/Ctrl+c.
             '\x1b' | '\x03' => self.stop(),
+            // Confirm all toggled matches on Enter.
+            '\r' if matches!(&self.hint, Some(hint) if hint.multi_select) => {
+                return self.confirm_selected();
+            },
             _ => (),
         }
 
@@ -124,30 +205,77 @@ impl HintState {
         self.update_matches(term);
 
         let hint = self.hint.as_ref()?;
+        let generator = HintLabels::new(&self.alphabet, HINT_SPLIT_PERCENTAGE);
+        let match_count = self.matches.len();
 
-        // Find the last label starting with the input character.
-        let mut labels = self.labels.iter().enumerate().rev();
-        let (index, label) = labels.find(|(_, label)| !label.is_empty() && label[0] == c)?;
+        let mut keys = self.keys.clone();
+        keys.push(c);
 
         // Check if the selected label is fully matched.
-        if label.len() == 1 {
-            let bounds = self.matches[index].clone();
-            let action = hint.action.clone();
+        if let Some(index) = generator.index(&keys).filter(|index| *index < match_count) {
+            let bounds = self.matches[match_count - 1 - index].clone();
+
+            // Toggle the match when multiple matches can be selected.
+            if hint.multi_select {
+                match self.selected.iter().position(|selected| *selected == bounds) {
+                    Some(position) => {
+                        self.selected.remove(position);
+                    },
+                    None => self.selected.push(bounds),
+                }
+                self.keys.clear();
+
+                return None;
+            }
+
+            let hint = hint.clone();
 
             self.stop();
 
-            Some(HintMatch { action, bounds })
-        } else {
-            // Store character to preserve the selection.
-            self.keys.push(c);
+            return Some(HintSelection::Single(HintMatch { hint, bounds }));
+        }
 
-            None
+        // Store character to preserve the selection, if there's any label starting with it.
+        let mut shortest_label = keys.clone();
+        shortest_label.push(generator.alphabet[0]);
+        if matches!(generator.index(&shortest_label), Some(index) if index < match_count) {
+            self.keys = keys;
+        }
+
+        None
+    }
+
+    /// Stop hint selection, returning all matches toggled in multi-select mode.
+    fn confirm_selected(&mut self) -> Option<HintSelection> {
+        let hint = self.hint.clone()?;
+
+        // Order matches by their position in the terminal.
+        let mut selected = mem::take(&mut self.selected);
+        selected.sort_by_key(|bounds| *bounds.start());
+
+        self.stop();
+
+        if selected.is_empty() {
+            return None;
         }
+
+        let matches =
+            selected.into_iter().map(|bounds| HintMatch { hint: hint.clone(), bounds }).collect();
+
+        Some(HintSelection::Multiple(matches))
     }
 
-    /// Hint key labels.
-    pub fn labels(&self) -> &Vec<Vec<char>> {
-        &self.labels
+    /// Remaining key label characters for the match at the specified index.
+    ///
+    /// Labels are only generated for matches inside the viewport.
+    pub fn label(&self, index: usize) -> Option<&[char]> {
+        let label = self.labels.get(index.checked_sub(self.label_offset)?)?;
+        Some(label)
+    }
+
+    /// Matches toggled in multi-select mode.
+    pub fn selected(&self) -> &[Match] {
+        &self.selected
     }
 
     /// Visible hint regex matches.
@@ -167,21 +295,136 @@ impl HintState {
 /// Hint match which was selected by the user.
 #[derive(PartialEq, Debug, Clone)]
 pub struct HintMatch {
-    /// Action for handling the text.
-    pub action: HintAction,
+    /// Hint which produced the match.
+    pub hint: Hint,
 
     /// Terminal range matching the hint.
     pub bounds: Match,
 }
 
+/// Hint matches which were selected using the keyboard.
+#[derive(PartialEq, Debug, Clone)]
+pub enum HintSelection {
+    /// A single hint match.
+    Single(HintMatch),
+
+    /// All matches toggled in multi-select mode.
+    Multiple(Vec<HintMatch>),
+}
+
+/// Captured output of a hint command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HintOutput {
+    /// Description of the command and its exit status.
+    title: String,
+
+    /// Text written to stdout, without trailing newlines.
+    stdout: String,
+
+    /// Lines written to stdout and stderr.
+    lines: Vec<String>,
+
+    /// Command completed successfully.
+    success: bool,
+
+    /// Output is shown in the overlay.
+    visible: bool,
+
+    /// Index of the first line shown in the overlay.
+    scroll: usize,
+}
+
+impl HintOutput {
+    pub fn new(program: &str, output: io::Result<Output>) -> Self {
+        let output = match output {
+            Ok(output) => output,
+            Err(err) => {
+                let title = match err.kind() {
+                    io::ErrorKind::TimedOut => format!("{} {}", program, err),
+                    _ => format!("Unable to launch {}: {}", program, err),
+                };
+
+                return Self {
+                    title,
+                    stdout: String::new(),
+                    lines: Vec::new(),
+                    success: false,
+                    visible: false,
+                    scroll: 0,
+                };
+            },
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let lines = stdout
+            .lines()
+            .chain(stderr.lines())
+            .map(|line| {
+                // Remove control characters which cannot be rendered.
+                line.replace('\t', "    ").chars().filter(|c| !c.is_control()).collect()
+            })
+            .collect();
+
+        let title = match output.status.code() {
+            Some(code) => format!("{} exited with status {}", program, code),
+            None => format!("{} was terminated by a signal", program),
+        };
+
+        // Strip trailing newlines to avoid executing the output when it's pasted.
+        let stdout = stdout.trim_end_matches(&['\r', '\n'][..]).to_owned();
+
+        Self { title, stdout, lines, success: output.status.success(), visible: false, scroll: 0 }
+    }
+
+    /// Description of the command and its exit status.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Text written to stdout, without trailing newlines.
+    pub fn stdout(&self) -> &str {
+        &self.stdout
+    }
+
+    /// Lines written to stdout and stderr.
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Check if the command completed successfully.
+    pub fn success(&self) -> bool {
+        self.success
+    }
+
+    /// Check if the output is shown in the overlay.
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Show or hide the output overlay.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Index of the first line shown in the overlay.
+    pub fn scroll(&self) -> usize {
+        self.scroll
+    }
+
+    /// Scroll the overlay by a number of lines, keeping a full page of lines visible.
+    pub fn scroll_by(&mut self, delta: isize, page_lines: usize) {
+        let max_scroll = self.lines.len().saturating_sub(page_lines);
+        let scroll = self.scroll as isize + delta;
+        self.scroll = min(max(scroll, 0) as usize, max_scroll);
+    }
+}
+
 /// Generator for creating new hint labels.
 struct HintLabels {
     /// Full character set available.
     alphabet: Vec<char>,
 
-    /// Alphabet indices for the next label.
-    indices: Vec<usize>,
-
     /// Point separating the alphabet's head and tail characters.
     ///
     /// To make identification of the tail character easy, part of the alphabet cannot be used for
@@ -201,41 +444,63 @@ impl HintLabels {
         let alphabet: Vec<char> = alphabet.into().chars().collect();
         let split_point = ((alphabet.len() - 1) as f32 * split_ratio.min(1.)) as usize;
 
-        Self { indices: vec![0], split_point, alphabet }
+        Self { split_point, alphabet }
     }
 
-    /// Get the characters for the next label.
-    fn next(&mut self) -> Vec<char> {
-        let characters = self.indices.iter().rev().map(|index| self.alphabet[*index]).collect();
-        self.increment();
+    /// Get the characters for the label at the specified position.
+    ///
+    /// Labels are ordered by their length, so the shortest labels have the lowest positions. Each
+    /// label is computed directly, without generating all previous labels first.
+    fn label(&self, mut index: usize) -> Vec<char> {
+        let head_len = self.split_point + 1;
+        let tail_len = self.alphabet.len() - head_len;
+
+        // Find the label length and the position within all labels of that length.
+        let mut prefix_len = 0;
+        let mut label_count = head_len;
+        while index >= label_count {
+            index -= label_count;
+            label_count = label_count.saturating_mul(tail_len);
+            prefix_len += 1;
+        }
+
+        let mut characters = vec![self.alphabet[index % head_len]];
+        index /= head_len;
+        for _ in 0..prefix_len {
+            characters.push(self.alphabet[head_len + index % tail_len]);
+            index /= tail_len;
+        }
+        characters.reverse();
+
         characters
     }
 
-    /// Increment the character sequence.
-    fn increment(&mut self) {
-        // Increment the last character; if it's not at the split point we're done.
-        let tail = &mut self.indices[0];
-        if *tail < self.split_point {
-            *tail += 1;
-            return;
-        }
-        *tail = 0;
-
-        // Increment all other characters in reverse order.
-        let alphabet_len = self.alphabet.len();
-        for index in self.indices.iter_mut().skip(1) {
-            if *index + 1 == alphabet_len {
-                // Reset character and move to the next if it's already at the limit.
-                *index = self.split_point + 1;
-            } else {
-                // If the character can be incremented, we're done.
-                *index += 1;
-                return;
-            }
+    /// Get the position of a label.
+    ///
+    /// This is the inverse of [`HintLabels::label`], returning `None` if the characters are not a
+    /// valid label.
+    fn index(&self, label: &[char]) -> Option<usize> {
+        let head_len = self.split_point + 1;
+        let tail_len = self.alphabet.len() - head_len;
+        let position = |c: &char| self.alphabet.iter().position(|a| a == c);
+
+        let (last, prefix) = label.split_last()?;
+        let mut index = position(last).filter(|position| *position < head_len)?;
+
+        // Add all labels with fewer characters.
+        let mut label_count = head_len;
+        let mut offset = 0;
+        let mut multiplier = head_len;
+        for c in prefix.iter().rev() {
+            let position = position(c).filter(|position| *position >= head_len)?;
+            index = index.checked_add((position - head_len).checked_mul(multiplier)?)?;
+            multiplier = multiplier.checked_mul(tail_len)?;
+
+            offset += label_count;
+            label_count = label_count.checked_mul(tail_len)?;
         }
 
-        // Extend the sequence with another character when nothing could be incremented.
-        self.indices.push(self.split_point + 1);
+        offset.checked_add(index)
     }
 }
 
@@ -280,11 +545,175 @@ pub fn highlighted_at<T>(
                 Some(regex_match)
             };
 
-            regex_match.map(|bounds| HintMatch { action: hint.action.clone(), bounds })
-        })
+            regex_match.map(|bounds| HintMatch { hint: hint.clone(), bounds })
+        })?
     })
 }
 
+/// Terminal state during a search of the scrollback history.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ScrollbackState {
+    scrolled_lines: usize,
+    history_generation: usize,
+    alt_screen: bool,
+}
+
+impl ScrollbackState {
+    pub fn new<T>(term: &Term<T>) -> Self {
+        Self {
+            scrolled_lines: term.grid().scrolled_lines(),
+            history_generation: term.grid().history_generation(),
+            alt_screen: term.mode().contains(TermMode::ALT_SCREEN),
+        }
+    }
+
+    /// Number of lines scrolled into the history since an earlier state.
+    ///
+    /// Returns `None` if the lines in the history might have changed in any other way.
+    pub fn scrolled_since(&self, old: &Self) -> Option<usize> {
+        if self.history_generation == old.history_generation && self.alt_screen == old.alt_screen {
+            Some(self.scrolled_lines.wrapping_sub(old.scrolled_lines))
+        } else {
+            None
+        }
+    }
+}
+
+/// Move matches up by the number of lines scrolled since they were found.
+///
+/// Since lines in the history do not change, only matches which were inside the screen area might
+/// be outdated. These are removed and the point at which searching has to continue is returned.
+fn shift_matches<T>(matches: &mut Vec<Match>, term: &Term<T>, scrolled: usize) -> Point {
+    let topmost_line = term.topmost_line();
+    let scrolled = min(scrolled, term.total_lines()) as i32;
+
+    for rm in matches.iter_mut() {
+        let start = Point::new(rm.start().line - scrolled, rm.start().column);
+        let end = Point::new(rm.end().line - scrolled, rm.end().column);
+        *rm = start..=end;
+    }
+
+    // Remove matches which were pushed out of the history.
+    let removed = matches.iter().take_while(|rm| rm.start().line < topmost_line).count();
+    matches.drain(..removed);
+
+    // Remove matches which might have changed, starting with the previous screen area.
+    let screen_start = max(Line(-scrolled), topmost_line);
+    let mut start = term.line_search_left(Point::new(screen_start, Column(0)));
+    while let Some(rm) = matches.last().filter(|rm| *rm.end() >= start) {
+        start = min(start, *rm.start());
+        matches.pop();
+    }
+
+    start
+}
+
+/// Apply the hint's post-processing to all matches, if it is enabled.
+fn post_process<T>(
+    hint: &Hint,
+    term: &Term<T>,
+    regex: &RegexSearch,
+    matches: Vec<Match>,
+) -> Vec<Match> {
+    if hint.post_processing {
+        matches
+            .into_iter()
+            .map(|rm| HintPostProcessor::new(term, regex, rm).collect::<Vec<_>>())
+            .flatten()
+            .collect()
+    } else {
+        matches
+    }
+}
+
+/// Get the arguments for launching a hint's command.
+///
+/// Arguments can reference the hint regex's capture groups using `{0}`, `{1}` or `{name}` for
+/// named groups, with `{{` and `}}` producing literal braces. If no argument references any
+/// capture group, the matched text is appended as the last argument instead.
+pub fn command_args(args: &[String], regex: &LazyRegex, text: &str) -> Vec<String> {
+    // Avoid compiling the regex when no argument could contain a template.
+    if args.iter().all(|arg| !arg.contains('{')) {
+        let mut args = args.to_vec();
+        args.push(text.to_owned());
+        return args;
+    }
+
+    let captures = regex.with_captures(|regex| regex.captures(text)).flatten();
+
+    // Get the text of a capture group.
+    let group = |name: &str| -> String {
+        let group = match (&captures, name.parse::<usize>()) {
+            (Some(captures), Ok(index)) => captures.get(index),
+            (Some(captures), Err(_)) => captures.name(name),
+            // Fall back to the entire text if the text cannot be matched again.
+            (None, Ok(0)) => return text.to_owned(),
+            (None, _) => None,
+        };
+        group.map_or_else(String::new, |group| group.as_str().to_owned())
+    };
+
+    let mut has_groups = false;
+    let mut expanded_args: Vec<String> = args
+        .iter()
+        .map(|arg| {
+            let (expanded, arg_has_groups) = expand_template(arg, group);
+            has_groups |= arg_has_groups;
+            expanded
+        })
+        .collect();
+
+    if !has_groups {
+        expanded_args.push(text.to_owned());
+    }
+
+    expanded_args
+}
+
+/// Replace all capture group references in a template.
+///
+/// The returned [`bool`] will be `true` if the template contained any capture group reference.
+fn expand_template<F>(template: &str, group: F) -> (String, bool)
+where
+    F: Fn(&str) -> String,
+{
+    let mut expanded = String::with_capacity(template.len());
+    let mut has_groups = false;
+
+    let mut rest = template;
+    while let Some(index) = rest.find(&['{', '}'][..]) {
+        expanded.push_str(&rest[..index]);
+        rest = &rest[index..];
+
+        // Replace escaped braces.
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            expanded.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+
+        // Replace valid capture group references.
+        let end = rest.find('}').filter(|_| rest.starts_with('{'));
+        let name = end.map(|end| &rest[1..end]).filter(|name| {
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+        match (name, end) {
+            (Some(name), Some(end)) => {
+                expanded.push_str(&group(name));
+                has_groups = true;
+                rest = &rest[end + 1..];
+            },
+            _ => {
+                expanded.push_str(&rest[..1]);
+                rest = &rest[1..];
+            },
+        }
+    }
+    expanded.push_str(rest);
+
+    (expanded, has_groups)
+}
+
 /// Iterator over all post-processed matches inside an existing hint match.
 struct HintPostProcessor<'a, T> {
     /// Regex search DFAs.
@@ -406,40 +835,104 @@ mod tests {
 
     #[test]
     fn hint_label_generation() {
-        let mut generator = HintLabels::new("0123", 0.5);
-
-        assert_eq!(generator.next(), vec!['0']);
-        assert_eq!(generator.next(), vec!['1']);
-
-        assert_eq!(generator.next(), vec!['2', '0']);
-        assert_eq!(generator.next(), vec!['2', '1']);
-        assert_eq!(generator.next(), vec!['3', '0']);
-        assert_eq!(generator.next(), vec!['3', '1']);
-
-        assert_eq!(generator.next(), vec!['2', '2', '0']);
-        assert_eq!(generator.next(), vec!['2', '2', '1']);
-        assert_eq!(generator.next(), vec!['2', '3', '0']);
-        assert_eq!(generator.next(), vec!['2', '3', '1']);
-        assert_eq!(generator.next(), vec!['3', '2', '0']);
-        assert_eq!(generator.next(), vec!['3', '2', '1']);
-        assert_eq!(generator.next(), vec!['3', '3', '0']);
-        assert_eq!(generator.next(), vec!['3', '3', '1']);
-
-        assert_eq!(generator.next(), vec!['2', '2', '2', '0']);
-        assert_eq!(generator.next(), vec!['2', '2', '2', '1']);
-        assert_eq!(generator.next(), vec!['2', '2', '3', '0']);
-        assert_eq!(generator.next(), vec!['2', '2', '3', '1']);
-        assert_eq!(generator.next(), vec!['2', '3', '2', '0']);
-        assert_eq!(generator.next(), vec!['2', '3', '2', '1']);
-        assert_eq!(generator.next(), vec!['2', '3', '3', '0']);
-        assert_eq!(generator.next(), vec!['2', '3', '3', '1']);
-        assert_eq!(generator.next(), vec!['3', '2', '2', '0']);
-        assert_eq!(generator.next(), vec!['3', '2', '2', '1']);
-        assert_eq!(generator.next(), vec!['3', '2', '3', '0']);
-        assert_eq!(generator.next(), vec!['3', '2', '3', '1']);
-        assert_eq!(generator.next(), vec!['3', '3', '2', '0']);
-        assert_eq!(generator.next(), vec!['3', '3', '2', '1']);
-        assert_eq!(generator.next(), vec!['3', '3', '3', '0']);
-        assert_eq!(generator.next(), vec!['3', '3', '3', '1']);
+        let generator = HintLabels::new("0123", 0.5);
+        let mut labels = (0..).map(|index| generator.label(index));
+
+        assert_eq!(labels.next(), Some(vec!['0']));
+        assert_eq!(labels.next(), Some(vec!['1']));
+
+        assert_eq!(labels.next(), Some(vec!['2', '0']));
+        assert_eq!(labels.next(), Some(vec!['2', '1']));
+        assert_eq!(labels.next(), Some(vec!['3', '0']));
+        assert_eq!(labels.next(), Some(vec!['3', '1']));
+
+        assert_eq!(labels.next(), Some(vec!['2', '2', '0']));
+        assert_eq!(labels.next(), Some(vec!['2', '2', '1']));
+        assert_eq!(labels.next(), Some(vec!['2', '3', '0']));
+        assert_eq!(labels.next(), Some(vec!['2', '3', '1']));
+        assert_eq!(labels.next(), Some(vec!['3', '2', '0']));
+        assert_eq!(labels.next(), Some(vec!['3', '2', '1']));
+        assert_eq!(labels.next(), Some(vec!['3', '3', '0']));
+        assert_eq!(labels.next(), Some(vec!['3', '3', '1']));
+
+        assert_eq!(labels.next(), Some(vec!['2', '2', '2', '0']));
+        assert_eq!(labels.next(), Some(vec!['2', '2', '2', '1']));
+        assert_eq!(labels.next(), Some(vec!['2', '2', '3', '0']));
+        assert_eq!(labels.next(), Some(vec!['2', '2', '3', '1']));
+        assert_eq!(labels.next(), Some(vec!['2', '3', '2', '0']));
+        assert_eq!(labels.next(), Some(vec!['2', '3', '2', '1']));
+        assert_eq!(labels.next(), Some(vec!['2', '3', '3', '0']));
+        assert_eq!(labels.next(), Some(vec!['2', '3', '3', '1']));
+        assert_eq!(labels.next(), Some(vec!['3', '2', '2', '0']));
+        assert_eq!(labels.next(), Some(vec!['3', '2', '2', '1']));
+        assert_eq!(labels.next(), Some(vec!['3', '2', '3', '0']));
+        assert_eq!(labels.next(), Some(vec!['3', '2', '3', '1']));
+        assert_eq!(labels.next(), Some(vec!['3', '3', '2', '0']));
+        assert_eq!(labels.next(), Some(vec!['3', '3', '2', '1']));
+        assert_eq!(labels.next(), Some(vec!['3', '3', '3', '0']));
+        assert_eq!(labels.next(), Some(vec!['3', '3', '3', '1']));
+    }
+
+    #[test]
+    fn hint_command_templates() {
+        let regex = LazyRegex::from(r"(?P<file>[\w/.]+):(?P<line>\d+)");
+        let args = vec![String::from("+{line}"), String::from("{file}")];
+        assert_eq!(command_args(&args, &regex, "src/main.rs:42"), vec!["+42", "src/main.rs"]);
+
+        let args = vec![String::from("--match={0}"), String::from("{{{2}}}")];
+        let expected = vec!["--match=src/main.rs:42", "{42}"];
+        assert_eq!(command_args(&args, &regex, "src/main.rs:42"), expected);
+
+        let args = vec![String::from("{{}}"), String::from("{not a group}")];
+        let expected = vec!["{}", "{not a group}", "src/main.rs:42"];
+        assert_eq!(command_args(&args, &regex, "src/main.rs:42"), expected);
+
+        let args = vec![String::from("{missing}")];
+        assert_eq!(command_args(&args, &regex, "src/main.rs:42"), vec![""]);
+
+        // Capture groups are extracted from a match of the entire text.
+        let regex = LazyRegex::from(r"(?P<short>a)|ab");
+        let args = vec![String::from("{0}"), String::from("{short}")];
+        assert_eq!(command_args(&args, &regex, "ab"), vec!["ab", ""]);
+    }
+
+    #[test]
+    fn hint_output_scrolling() {
+        let lines = (0..10).map(|i| i.to_string()).collect();
+        let mut output = HintOutput {
+            title: String::new(),
+            stdout: String::new(),
+            lines,
+            success: true,
+            visible: true,
+            scroll: 0,
+        };
+
+        output.scroll_by(-1, 4);
+        assert_eq!(output.scroll(), 0);
+
+        output.scroll_by(3, 4);
+        assert_eq!(output.scroll(), 3);
+
+        output.scroll_by(10, 4);
+        assert_eq!(output.scroll(), 6);
+
+        output.scroll_by(-10, 20);
+        assert_eq!(output.scroll(), 0);
+    }
+
+    #[test]
+    fn hint_label_index() {
+        let generator = HintLabels::new("0123", 0.5);
+
+        for index in 0..1000 {
+            assert_eq!(generator.index(&generator.label(index)), Some(index));
+        }
+
+        assert_eq!(generator.index(&['2', '2', '1']), Some(7));
+        assert_eq!(generator.index(&['0', '1']), None);
+        assert_eq!(generator.index(&['2']), None);
+        assert_eq!(generator.index(&['x']), None);
+        assert_eq!(generator.index(&[]), None);
     }
 }
@@ -0,0 +1,131 @@
+use crate::config::KeyPress;
+
+/// Progress through multi-key bindings and key tables.
+#[derive(Default, Debug)]
+pub struct KeySequence {
+    /// Keys pressed so far, which are the prefix of at least one binding.
+    keys: Vec<KeyPress>,
+
+    /// Name of the active key table.
+    table: Option<String>,
+
+    /// Keep the key table active after a binding was triggered.
+    locked: bool,
+}
+
+impl KeySequence {
+    /// Check if a key sequence is in progress or a key table is active.
+    #[inline]
+    pub fn active(&self) -> bool {
+        !self.keys.is_empty() || self.table.is_some()
+    }
+
+    /// Keys pressed so far.
+    #[inline]
+    pub fn keys(&self) -> &[KeyPress] {
+        &self.keys
+    }
+
+    /// Name of the active key table.
+    #[inline]
+    pub fn table(&self) -> Option<&str> {
+        self.table.as_deref()
+    }
+
+    /// Add a key to the pending sequence.
+    pub fn push(&mut self, key: KeyPress) {
+        self.keys.push(key);
+    }
+
+    /// Activate a key table.
+    ///
+    /// Unless the table is locked, it will be left again after the next binding was triggered.
+    pub fn enter_table(&mut self, table: String, locked: bool) {
+        self.keys.clear();
+        self.table = Some(table);
+        self.locked = locked;
+    }
+
+    /// Complete the pending sequence after a binding was triggered or no binding matched.
+    pub fn finish(&mut self) {
+        self.keys.clear();
+
+        if !self.locked {
+            self.table = None;
+        }
+    }
+
+    /// Abort the pending sequence and leave the active key table.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Text shown in the key sequence indicator.
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+
+        if let Some(table) = &self.table {
+            label.push('[');
+            label.push_str(table);
+            label.push(']');
+        }
+
+        for key in &self.keys {
+            if !label.is_empty() {
+                label.push(' ');
+            }
+            label.push_str(&key.to_string());
+        }
+
+        label
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use glutin::event::{ModifiersState, VirtualKeyCode};
+
+    use crate::config::{Key, ModsWrapper};
+
+    #[test]
+    fn one_shot_table() {
+        let mut sequence = KeySequence::default();
+        assert!(!sequence.active());
+
+        sequence.enter_table(String::from("prefix"), false);
+        assert_eq!(sequence.table(), Some("prefix"));
+
+        sequence.finish();
+        assert!(!sequence.active());
+    }
+
+    #[test]
+    fn locked_table() {
+        let mut sequence = KeySequence::default();
+        sequence.enter_table(String::from("resize"), true);
+        sequence
+            .push(KeyPress { key: Key::Keycode(VirtualKeyCode::A), mods: ModsWrapper::default() });
+
+        sequence.finish();
+        assert_eq!(sequence.table(), Some("resize"));
+        assert!(sequence.keys().is_empty());
+
+        sequence.reset();
+        assert!(!sequence.active());
+    }
+
+    #[test]
+    fn sequence_label() {
+        let mut sequence = KeySequence::default();
+        sequence.enter_table(String::from("tmux"), false);
+        sequence.push(KeyPress {
+            key: Key::Keycode(VirtualKeyCode::A),
+            mods: ModsWrapper(ModifiersState::CTRL | ModifiersState::SHIFT),
+        });
+        sequence.push(KeyPress { key: Key::Scancode(0x1c), mods: ModsWrapper::default() });
+
+        assert_eq!(sequence.label(), "[tmux] Ctrl+Shift+A 0x1c");
+    }
+}
@@ -29,6 +29,7 @@ use alacritty_terminal::grid::Dimensions as _;
 use alacritty_terminal::index::{Column, Direction, Line, Point};
 use alacritty_terminal::selection::Selection;
 use alacritty_terminal::term::cell::Flags;
+use alacritty_terminal::term::search::SearchFlags;
 use alacritty_terminal::term::{SizeInfo, Term, TermMode, MIN_COLUMNS, MIN_SCREEN_LINES};
 
 use crate::config::font::Font;
@@ -40,21 +41,27 @@ use crate::display::bell::VisualBell;
 use crate::display::color::List;
 use crate::display::content::RenderableContent;
 use crate::display::cursor::IntoRects;
-use crate::display::hint::{HintMatch, HintState};
+use crate::display::hint::{HintMatch, HintOutput, HintState};
+use crate::display::key_sequence::KeySequence;
 use crate::display::meter::Meter;
+use crate::display::picker::Picker;
 use crate::display::window::Window;
-use crate::event::{Mouse, SearchState};
+use crate::event::{Mouse, SearchFilter, SearchState};
 use crate::message_bar::{MessageBuffer, MessageType};
 use crate::renderer::rects::{RenderLines, RenderRect};
 use crate::renderer::{self, GlyphCache, QuadRenderer};
 
+pub mod char_picker;
+pub mod clipboard_history;
+pub mod color;
 pub mod content;
 pub mod cursor;
 pub mod hint;
+pub mod key_sequence;
+pub mod picker;
 pub mod window;
 
 mod bell;
-mod color;
 mod meter;
 #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
 mod wayland_theme;
@@ -63,10 +70,10 @@ mod wayland_theme;
 pub const MAX_SEARCH_LINES: usize = 100;
 
 /// Label for the forward terminal search bar.
-const FORWARD_SEARCH_LABEL: &str = "Search: ";
+const FORWARD_SEARCH_LABEL: &str = "Search";
 
 /// Label for the backward terminal search bar.
-const BACKWARD_SEARCH_LABEL: &str = "Backward Search: ";
+const BACKWARD_SEARCH_LABEL: &str = "Backward Search";
 
 #[derive(Debug)]
 pub enum Error {
@@ -195,6 +202,15 @@ pub struct Display {
     /// State of the keyboard hints.
     pub hint_state: HintState,
 
+    /// Output of the last hint command with captured output.
+    pub hint_output: Option<HintOutput>,
+
+    /// Overlay for choosing clipboard history entries or Unicode characters.
+    pub picker: Option<Picker>,
+
+    /// Pending multi-key binding and active key table.
+    pub key_sequence: KeySequence,
+
     renderer: QuadRenderer,
     glyph_cache: GlyphCache,
     meter: Meter,
@@ -338,6 +354,9 @@ impl Display {
             renderer,
             glyph_cache,
             hint_state,
+            hint_output: None,
+            picker: None,
+            key_sequence: Default::default(),
             meter: Meter::new(),
             size_info,
             highlighted_hint: None,
@@ -461,6 +480,7 @@ impl Display {
 
         // Resize terminal.
         terminal.resize(self.size_info);
+        self.hint_state.invalidate_matches();
 
         // Resize renderer.
         let physical =
@@ -483,9 +503,10 @@ impl Display {
         message_buffer: &MessageBuffer,
         config: &Config,
         search_state: &SearchState,
+        mark_point: Option<Point>,
     ) {
         // Collect renderable content before the terminal is dropped.
-        let mut content = RenderableContent::new(config, self, &terminal, search_state);
+        let mut content = RenderableContent::new(config, self, &terminal, search_state, mark_point);
         let mut grid_cells = Vec::new();
         while let Some(cell) = content.next() {
             grid_cells.push(cell);
@@ -502,6 +523,10 @@ impl Display {
         let vi_mode = terminal.mode().contains(TermMode::VI);
         let vi_mode_cursor = if vi_mode { Some(terminal.vi_mode_cursor) } else { None };
 
+        // Collect the text of all lines visible in the search filter view.
+        let filter_rows =
+            search_state.filter().map(|filter| Self::filter_rows(&terminal, &size_info, filter));
+
         // Drop terminal as early as possible to free lock.
         drop(terminal);
 
@@ -511,8 +536,19 @@ impl Display {
 
         let mut lines = RenderLines::new();
 
-        // Draw grid.
-        {
+        let overlay_visible =
+            filter_rows.is_some() || self.hint_output_visible() || self.picker.is_some();
+
+        if self.picker.is_some() {
+            // Draw the picker instead of the grid.
+            self.draw_picker(config, &size_info);
+        } else if let Some(filter_rows) = &filter_rows {
+            // Draw only the lines matching the search instead of the grid.
+            self.draw_filter(config, &size_info, filter_rows);
+        } else if self.hint_output_visible() {
+            // Draw the output of the last hint command instead of the grid.
+            self.draw_hint_output(config, &size_info);
+        } else {
             let _sampler = self.meter.sampler();
 
             let glyph_cache = &mut self.glyph_cache;
@@ -540,18 +576,27 @@ impl Display {
 
         let mut rects = lines.rects(&metrics, &size_info);
 
-        if let Some(vi_mode_cursor) = vi_mode_cursor {
+        if let Some(vi_mode_cursor) = vi_mode_cursor.filter(|_| !overlay_visible) {
             // Indicate vi mode by showing the cursor's position in the top right corner.
             let vi_point = vi_mode_cursor.point;
             let line = (-vi_point.line.0 + size_info.bottommost_line().0) as usize;
             self.draw_line_indicator(config, &size_info, total_lines, Some(vi_point), line);
-        } else if search_state.regex().is_some() {
+        } else if search_state.regex().is_some() && !overlay_visible {
             // Show current display offset in vi-less search to indicate match position.
             self.draw_line_indicator(config, &size_info, total_lines, None, display_offset);
+        } else if let Some(mark_point) = mark_point.filter(|_| !overlay_visible) {
+            // Indicate mark mode in the top right corner.
+            let mark_point = point_to_viewport(display_offset, mark_point);
+            self.draw_mark_indicator(config, &size_info, mark_point);
+        }
+
+        // Show the pending key sequence in the bottom right corner.
+        if self.key_sequence.active() {
+            self.draw_key_sequence(config, &size_info);
         }
 
         // Push the cursor rects for rendering.
-        if let Some(cursor) = cursor {
+        if let Some(cursor) = cursor.filter(|_| !overlay_visible) {
             for rect in cursor.rects(&size_info, config.cursor.thickness()) {
                 rects.push(rect);
             }
@@ -582,6 +627,7 @@ impl Display {
             let bg = match message.ty() {
                 MessageType::Error => config.ui_config.colors.normal.red,
                 MessageType::Warning => config.ui_config.colors.normal.yellow,
+                MessageType::Info => config.ui_config.colors.normal.green,
             };
 
             let message_bar_rect =
@@ -616,8 +662,15 @@ impl Display {
                     Direction::Right => FORWARD_SEARCH_LABEL,
                     Direction::Left => BACKWARD_SEARCH_LABEL,
                 };
+                let mut search_label =
+                    Self::format_search_label(search_label, search_state.flags());
+
+                // Show the number of matches in front of the regex.
+                if let Some(count) = search_state.count() {
+                    search_label = format!("{}{} ", search_label, count);
+                }
 
-                let search_text = Self::format_search(&size_info, regex, search_label);
+                let search_text = Self::format_search(&size_info, regex, &search_label);
 
                 // Render the search bar.
                 self.draw_search(config, &size_info, &search_text);
@@ -705,6 +758,188 @@ impl Display {
         dirty
     }
 
+    /// Format the text of all visible lines in the search filter view.
+    ///
+    /// Every row consists of a gutter with the line's position in the scrollback, followed by the
+    /// line's text. The selected row is marked by the returned boolean.
+    fn filter_rows<T>(
+        terminal: &Term<T>,
+        size_info: &SizeInfo,
+        filter: &SearchFilter,
+    ) -> Vec<(String, String, bool)> {
+        let topmost_line = terminal.topmost_line();
+        let gutter_width = terminal.total_lines().to_string().len();
+        let text_width = size_info.columns().saturating_sub(gutter_width + 1);
+
+        (filter.scroll()..filter.len())
+            .take(size_info.screen_lines())
+            .map(|i| {
+                // Leave rows empty once their line is not part of the terminal anymore.
+                let start = match filter.line(i, terminal) {
+                    Some(line) => Point::new(line, Column(0)),
+                    None => return (String::new(), String::new(), i == filter.selected()),
+                };
+                let end = terminal.line_search_right(start);
+
+                // Add spacers for wide chars.
+                let mut text = String::new();
+                for c in terminal.bounds_to_string(start, end).chars() {
+                    text.push(c);
+                    if c.width() == Some(2) {
+                        text.push(' ');
+                    }
+                }
+                let text = text.chars().take(text_width).collect();
+
+                let index = (start.line - topmost_line).0 + 1;
+                let gutter = format!("{:>1$} ", index, gutter_width);
+
+                (gutter, text, i == filter.selected())
+            })
+            .collect()
+    }
+
+    /// Draw the search filter view.
+    fn draw_filter(
+        &mut self,
+        config: &Config,
+        size_info: &SizeInfo,
+        rows: &[(String, String, bool)],
+    ) {
+        let colors = &config.ui_config.colors;
+        let gutter_fg = colors.line_indicator.foreground.unwrap_or(colors.primary.background);
+        let gutter_bg = colors.line_indicator.background.unwrap_or(colors.primary.foreground);
+
+        let glyph_cache = &mut self.glyph_cache;
+        self.renderer.with_api(&config.ui_config, size_info, |mut api| {
+            for (line, (gutter, text, selected)) in rows.iter().enumerate() {
+                let (fg, bg) = if *selected {
+                    (colors.primary.background, colors.primary.foreground)
+                } else {
+                    (colors.primary.foreground, colors.primary.background)
+                };
+
+                let column = Column(gutter.chars().count());
+                api.render_string(
+                    glyph_cache,
+                    Point::new(line, Column(0)),
+                    gutter_fg,
+                    gutter_bg,
+                    gutter,
+                );
+
+                // Pad the selected line to highlight the entire row.
+                let text = format!("{:<1$}", text, size_info.columns().saturating_sub(column.0));
+                api.render_string(glyph_cache, Point::new(line, column), fg, bg, &text);
+            }
+        });
+    }
+
+    /// Check if the hint command output overlay is visible.
+    fn hint_output_visible(&self) -> bool {
+        matches!(&self.hint_output, Some(output) if output.visible())
+    }
+
+    /// Draw the hint command output overlay.
+    fn draw_hint_output(&mut self, config: &Config, size_info: &SizeInfo) {
+        let output = match &self.hint_output {
+            Some(output) => output,
+            None => return,
+        };
+
+        let colors = &config.ui_config.colors;
+        let title_fg = colors.line_indicator.foreground.unwrap_or(colors.primary.background);
+        let title_bg = colors.line_indicator.background.unwrap_or(colors.primary.foreground);
+        let (fg, bg) = (colors.primary.foreground, colors.primary.background);
+
+        let columns = size_info.columns();
+        let title = format!("{} (Enter: paste, Escape: close)", output.title());
+        let title = format!("{:<1$}", title, columns);
+        let rows = output.lines().iter().skip(output.scroll()).take(size_info.screen_lines() - 1);
+
+        let glyph_cache = &mut self.glyph_cache;
+        self.renderer.with_api(&config.ui_config, size_info, |mut api| {
+            api.render_string(glyph_cache, Point::new(0, Column(0)), title_fg, title_bg, &title);
+
+            for (line, text) in rows.enumerate() {
+                let text: String = text.chars().take(columns).collect();
+                api.render_string(glyph_cache, Point::new(line + 1, Column(0)), fg, bg, &text);
+            }
+        });
+    }
+
+    /// Draw the clipboard history or character picker overlay.
+    fn draw_picker(&mut self, config: &Config, size_info: &SizeInfo) {
+        let picker = match &self.picker {
+            Some(picker) => picker,
+            None => return,
+        };
+
+        let colors = &config.ui_config.colors;
+        let title_fg = colors.line_indicator.foreground.unwrap_or(colors.primary.background);
+        let title_bg = colors.line_indicator.background.unwrap_or(colors.primary.foreground);
+
+        let columns = size_info.columns();
+        let title: String =
+            format!("{:<1$}", picker.title(), columns).chars().take(columns).collect();
+
+        let rows = picker
+            .labels()
+            .enumerate()
+            .skip(picker.scroll())
+            .take(size_info.screen_lines().saturating_sub(1))
+            .map(|(i, label)| {
+                let text: String = label
+                    .chars()
+                    .flat_map(|c| {
+                        // Add spacers for wide chars.
+                        let spacer = if c.width() == Some(2) { Some(' ') } else { None };
+                        Some(c).into_iter().chain(spacer)
+                    })
+                    .take(columns)
+                    .collect();
+                (format!("{:<1$}", text, columns), i == picker.selected())
+            })
+            .collect::<Vec<_>>();
+
+        let glyph_cache = &mut self.glyph_cache;
+        self.renderer.with_api(&config.ui_config, size_info, |mut api| {
+            api.render_string(glyph_cache, Point::new(0, Column(0)), title_fg, title_bg, &title);
+
+            for (line, (text, selected)) in rows.iter().enumerate() {
+                let (fg, bg) = if *selected {
+                    (colors.primary.background, colors.primary.foreground)
+                } else {
+                    (colors.primary.foreground, colors.primary.background)
+                };
+
+                api.render_string(glyph_cache, Point::new(line + 1, Column(0)), fg, bg, text);
+            }
+        });
+    }
+
+    /// Format search label with all active search flags.
+    fn format_search_label(label: &str, flags: SearchFlags) -> String {
+        let flag_names = [
+            (SearchFlags::CASE_INSENSITIVE, "ignore case"),
+            (SearchFlags::SMART_CASE, "smart case"),
+            (SearchFlags::LITERAL, "literal"),
+            (SearchFlags::WHOLE_WORD, "word"),
+        ];
+
+        let active: Vec<&str> = flag_names
+            .iter()
+            .filter(|(flag, _)| flags.contains(*flag))
+            .map(|(_, name)| *name)
+            .collect();
+
+        if active.is_empty() {
+            format!("{}: ", label)
+        } else {
+            format!("{} ({}): ", label, active.join(", "))
+        }
+    }
+
     /// Format search regex to account for the cursor and fullwidth characters.
     fn format_search(size_info: &SizeInfo, search_regex: &str, search_label: &str) -> String {
         // Add spacers for wide chars.
@@ -795,6 +1030,52 @@ impl Display {
         }
     }
 
+    /// Draw the mark mode indicator, unless it would obscure the mark cursor.
+    fn draw_mark_indicator(
+        &mut self,
+        config: &Config,
+        size_info: &SizeInfo,
+        mark_point: Option<Point<usize>>,
+    ) {
+        let text = "[MARK]";
+        let column = Column(size_info.columns().saturating_sub(text.len()));
+        let colors = &config.ui_config.colors;
+        let fg = colors.line_indicator.foreground.unwrap_or(colors.primary.background);
+        let bg = colors.line_indicator.background.unwrap_or(colors.primary.foreground);
+
+        if let Some(point) = mark_point {
+            if point.line == 0 && point.column >= column {
+                return;
+            }
+        }
+
+        let glyph_cache = &mut self.glyph_cache;
+        self.renderer.with_api(&config.ui_config, size_info, |mut api| {
+            api.render_string(glyph_cache, Point::new(0, column), fg, bg, text);
+        });
+    }
+
+    /// Draw the pending key sequence indicator.
+    fn draw_key_sequence(&mut self, config: &Config, size_info: &SizeInfo) {
+        let label = self.key_sequence.label();
+        let columns = size_info.columns();
+
+        // Truncate the beginning of the label if it exceeds the viewport width.
+        let label_len = label.chars().count();
+        let text: String = label.chars().skip(label_len.saturating_sub(columns)).collect();
+        let column = Column(columns.saturating_sub(label_len));
+        let line = size_info.screen_lines().saturating_sub(1);
+
+        let colors = &config.ui_config.colors;
+        let fg = colors.line_indicator.foreground.unwrap_or(colors.primary.background);
+        let bg = colors.line_indicator.background.unwrap_or(colors.primary.foreground);
+
+        let glyph_cache = &mut self.glyph_cache;
+        self.renderer.with_api(&config.ui_config, size_info, |mut api| {
+            api.render_string(glyph_cache, Point::new(line, column), fg, bg, &text);
+        });
+    }
+
     /// Requst a new frame for a window on Wayland.
     #[inline]
     #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
@@ -0,0 +1,187 @@
+use std::cmp::min;
+
+use crate::display::{char_picker, clipboard_history};
+
+/// Items which can be chosen from a picker overlay.
+#[derive(Debug)]
+pub enum PickerItems {
+    /// All Unicode characters with a name.
+    Characters,
+
+    /// Clipboard history entries, most recent entry first.
+    ClipboardHistory(Vec<String>),
+}
+
+/// Item chosen from a picker overlay.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PickerItem<'a> {
+    Char(char),
+    Text(&'a str),
+}
+
+/// State of a list overlay which can be filtered by typing.
+#[derive(Debug)]
+pub struct Picker {
+    /// Items listed in the overlay.
+    items: PickerItems,
+
+    /// Text used for filtering the items.
+    query: String,
+
+    /// Indices of all items matching the query.
+    matches: Vec<usize>,
+
+    /// Index of the selected match.
+    selected: usize,
+
+    /// Index of the first match shown in the overlay.
+    scroll: usize,
+}
+
+impl Picker {
+    pub fn new(items: PickerItems) -> Self {
+        let mut picker =
+            Self { items, query: String::new(), matches: Vec::new(), selected: 0, scroll: 0 };
+        picker.update_matches();
+        picker
+    }
+
+    /// Index of the selected match.
+    #[inline]
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Index of the first match shown in the overlay.
+    #[inline]
+    pub fn scroll(&self) -> usize {
+        self.scroll
+    }
+
+    /// Title of the overlay, including the query.
+    pub fn title(&self) -> String {
+        match self.items {
+            PickerItems::Characters => {
+                format!("Characters: {}_ (Enter: insert, Escape: close)", self.query)
+            },
+            PickerItems::ClipboardHistory(_) => {
+                format!("Clipboard history: {}_ (Enter: paste, Escape: close)", self.query)
+            },
+        }
+    }
+
+    /// The selected item.
+    pub fn selected_item(&self) -> Option<PickerItem<'_>> {
+        self.matches.get(self.selected).map(|&index| self.item(index))
+    }
+
+    /// Iterate over the text describing every item matching the query.
+    pub fn labels(&self) -> impl Iterator<Item = String> + '_ {
+        self.matches.iter().map(move |&index| match &self.items {
+            PickerItems::Characters => char_picker::label(index),
+            PickerItems::ClipboardHistory(entries) => clipboard_history::label(&entries[index]),
+        })
+    }
+
+    /// Add a character to the query.
+    pub fn push_char(&mut self, c: char) {
+        self.query.push(c);
+        self.update_matches();
+    }
+
+    /// Remove the last character from the query.
+    pub fn pop_char(&mut self) {
+        self.query.pop();
+        self.update_matches();
+    }
+
+    /// Move the selection, keeping it within the `page_lines` visible lines.
+    pub fn select_by(&mut self, delta: isize, page_lines: usize) {
+        let max_selected = self.matches.len().saturating_sub(1) as isize;
+        self.selected = min(self.selected as isize + delta, max_selected).max(0) as usize;
+
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if page_lines > 0 && self.selected >= self.scroll + page_lines {
+            self.scroll = self.selected + 1 - page_lines;
+        }
+    }
+
+    /// Get an item by its index.
+    fn item(&self, index: usize) -> PickerItem<'_> {
+        match &self.items {
+            PickerItems::Characters => PickerItem::Char(char_picker::character(index)),
+            PickerItems::ClipboardHistory(entries) => PickerItem::Text(&entries[index]),
+        }
+    }
+
+    /// Update the matching items after a query change.
+    fn update_matches(&mut self) {
+        self.matches = match &self.items {
+            PickerItems::Characters => char_picker::search(&self.query),
+            PickerItems::ClipboardHistory(entries) => {
+                clipboard_history::search(entries, &self.query)
+            },
+        };
+
+        self.selected = 0;
+        self.scroll = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picker_filtering() {
+        let entries = vec![String::from("cargo test"), String::from("ls"), String::from("cat")];
+        let mut picker = Picker::new(PickerItems::ClipboardHistory(entries));
+
+        picker.select_by(1, 10);
+        assert_eq!(picker.selected_item(), Some(PickerItem::Text("ls")));
+
+        picker.push_char('c');
+        picker.push_char('t');
+        assert_eq!(picker.labels().collect::<Vec<_>>(), vec!["cargo test", "cat"]);
+        assert_eq!(picker.selected_item(), Some(PickerItem::Text("cargo test")));
+
+        picker.select_by(5, 10);
+        assert_eq!(picker.selected_item(), Some(PickerItem::Text("cat")));
+
+        picker.push_char('x');
+        assert_eq!(picker.selected_item(), None);
+
+        picker.pop_char();
+        picker.pop_char();
+        picker.pop_char();
+        assert_eq!(picker.labels().count(), 3);
+    }
+
+    #[test]
+    fn picker_scrolling() {
+        let entries = (0..10).map(|i| i.to_string()).collect();
+        let mut picker = Picker::new(PickerItems::ClipboardHistory(entries));
+
+        picker.select_by(5, 3);
+        assert_eq!((picker.selected(), picker.scroll()), (5, 3));
+
+        picker.select_by(-4, 3);
+        assert_eq!((picker.selected(), picker.scroll()), (1, 1));
+
+        picker.select_by(-10, 3);
+        assert_eq!((picker.selected(), picker.scroll()), (0, 0));
+    }
+
+    #[test]
+    fn picker_labels() {
+        let mut picker = Picker::new(PickerItems::Characters);
+        for c in "rightwards arrow".chars() {
+            picker.push_char(c);
+        }
+        assert_eq!(picker.labels().next().unwrap(), "→  U+2192  rightwards arrow");
+
+        let picker = Picker::new(PickerItems::ClipboardHistory(vec![String::from(" a\nb\t")]));
+        assert_eq!(picker.labels().collect::<Vec<_>>(), vec!["a↵b"]);
+    }
+}
@@ -5,11 +5,9 @@ use std::cmp::{max, min};
 use std::collections::VecDeque;
 use std::env;
 use std::f32;
-use std::fmt::Debug;
-#[cfg(not(any(target_os = "macos", windows)))]
-use std::fs;
+use std::fmt::{self, Debug, Formatter};
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, Write};
 use std::mem;
 use std::path::{Path, PathBuf};
 #[cfg(not(any(target_os = "macos", windows)))]
@@ -18,12 +16,14 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use glutin::dpi::PhysicalSize;
-use glutin::event::{ElementState, Event as GlutinEvent, ModifiersState, MouseButton, WindowEvent};
+use glutin::event::{
+    ElementState, Event as GlutinEvent, ModifiersState, MouseButton, VirtualKeyCode, WindowEvent,
+};
 use glutin::event_loop::{ControlFlow, EventLoop, EventLoopProxy, EventLoopWindowTarget};
 use glutin::platform::run_return::EventLoopExtRunReturn;
 #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
 use glutin::platform::unix::EventLoopWindowTargetExtUnix;
-use log::info;
+use log::{debug, info, warn};
 use serde_json as json;
 
 use crossfont::{self, Size};
@@ -34,24 +34,34 @@ use alacritty_terminal::grid::{Dimensions, Scroll};
 use alacritty_terminal::index::{Boundary, Column, Direction, Line, Point, Side};
 use alacritty_terminal::selection::{Selection, SelectionType};
 use alacritty_terminal::sync::FairMutex;
-use alacritty_terminal::term::search::{Match, RegexSearch};
+use alacritty_terminal::term::color::COUNT;
+use alacritty_terminal::term::search::{
+    CancelHandle, Match, RegexError, RegexIter, RegexSearch, SearchFlags,
+};
 use alacritty_terminal::term::{ClipboardType, SizeInfo, Term, TermMode};
-#[cfg(not(windows))]
-use alacritty_terminal::tty;
+use alacritty_terminal::thread;
+use alacritty_terminal::vi_mode::{ViModeCursor, ViMotion};
 
 use crate::cli::Options as CLIOptions;
-use crate::clipboard::Clipboard;
+use crate::clipboard::{self, Clipboard};
+use crate::config::osc52::Osc52Policy;
 use crate::config::ui_config::{HintAction, HintInternalAction};
 use crate::config::{self, Config};
-use crate::daemon::start_daemon;
-use crate::display::hint::HintMatch;
+use crate::copy_format::{CopyFormat, StyledText};
+use crate::daemon::{run_with_output, start_daemon};
+use crate::display::hint::{command_args, HintMatch, HintOutput, HintSelection, ScrollbackState};
+use crate::display::key_sequence::KeySequence;
+use crate::display::picker::{Picker, PickerItem, PickerItems};
 use crate::display::window::Window;
 use crate::display::{self, Display, DisplayUpdate};
+use crate::export::{self, ExportRegion};
+use crate::foreground_process::{self, ForegroundProcess};
 use crate::input::{self, ActionContext as _, FONT_SIZE_STEP};
-#[cfg(target_os = "macos")]
-use crate::macos;
-use crate::message_bar::{Message, MessageBuffer};
+use crate::macros::Macros;
+use crate::message_bar::{Message, MessageBuffer, MessageType};
+use crate::paste;
 use crate::scheduler::{Scheduler, TimerId};
+use crate::search_history;
 
 /// Duration after the last user input until an unlimited search is performed.
 pub const TYPING_SEARCH_DELAY: Duration = Duration::from_millis(500);
@@ -62,6 +72,39 @@ const MAX_SEARCH_WHILE_TYPING: Option<usize> = Some(1000);
 /// Maximum number of search terms stored in the history.
 const MAX_SEARCH_HISTORY_SIZE: usize = 255;
 
+/// Number of lines searched for counting matches before unlocking the terminal.
+const SEARCH_COUNT_LINES: usize = 1000;
+
+/// Duration after the last terminal update until search matches are counted again.
+const SEARCH_COUNT_DELAY: Duration = Duration::from_millis(250);
+
+/// Maximum duration after the first terminal update until search matches are counted again.
+const SEARCH_COUNT_MAX_DELAY: Duration = Duration::from_secs(1);
+
+/// Maximum number of search matches which will be counted.
+const MAX_SEARCH_COUNT: usize = 10_000;
+
+/// Duration after which hint commands capturing their output are killed.
+const HINT_COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximum number of bytes captured from each output stream of a hint command.
+const MAX_HINT_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// Message bar target for the output of hint commands.
+const HINT_OUTPUT_MESSAGE_TARGET: &str = "hint_output";
+
+/// Message bar target for confirmation prompts.
+const CONFIRMATION_MESSAGE_TARGET: &str = "confirmation";
+
+/// Message bar target for the clipboard history entry restored by cycling.
+const CLIPBOARD_HISTORY_MESSAGE_TARGET: &str = "clipboard_history";
+
+/// Message bar target for the macro recording indicator.
+const MACRO_MESSAGE_TARGET: &str = "macro_recording";
+
+/// Maximum number of characters of clipboard content shown in the message bar.
+const CLIPBOARD_PREVIEW_LENGTH: usize = 80;
+
 /// Events dispatched through the UI event loop.
 #[derive(Debug, Clone)]
 pub enum Event {
@@ -72,6 +115,12 @@ pub enum Event {
     Message(Message),
     BlinkCursor,
     SearchNext,
+    SearchCount,
+    SearchMatches(Box<SearchMatches>),
+    SearchCompiled(Box<Result<RegexSearch, RegexError>>),
+    HintRegexCompiled,
+    HintOutput(Box<HintOutput>),
+    KeySequenceTimeout,
 }
 
 impl From<Event> for GlutinEvent<'_, Event> {
@@ -86,6 +135,202 @@ impl From<TerminalEvent> for Event {
     }
 }
 
+/// Progress of counting all matches of the active search.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SearchCount {
+    /// Number of matches found so far.
+    total: usize,
+
+    /// One-based index of the focused match.
+    focused: Option<usize>,
+
+    /// All matches have been counted.
+    complete: bool,
+}
+
+/// Batch of matches found while counting all matches of the active search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatches {
+    /// Counting pass which found the matches.
+    pass: usize,
+
+    /// Start of each match and of the logical line containing it.
+    starts: Vec<(Point, Line)>,
+
+    /// Terminal state while searching for the matches.
+    ///
+    /// Once the history changed in any other way than by scrolling new lines into it, all previous
+    /// batches are invalidated.
+    scrollback: ScrollbackState,
+
+    /// All matches have been counted.
+    complete: bool,
+}
+
+impl fmt::Display for SearchCount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.focused {
+            Some(focused) => write!(f, "[{}/{}", focused, self.total)?,
+            None => write!(f, "[?/{}", self.total)?,
+        }
+
+        if !self.complete {
+            write!(f, "+")?;
+        }
+
+        write!(f, "]")
+    }
+}
+
+/// Filtered view of all lines containing a search match.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct SearchFilter {
+    /// Start of all matching logical lines.
+    lines: Vec<Line>,
+
+    /// Terminal state at the time the lines were found.
+    scrollback: Option<ScrollbackState>,
+
+    /// Index of the selected line.
+    selected: usize,
+
+    /// Index of the first line visible in the viewport.
+    scroll: usize,
+}
+
+impl SearchFilter {
+    /// Number of matching logical lines.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Current start of a matching logical line.
+    ///
+    /// Returns `None` if the line is not part of the terminal anymore.
+    pub fn line<T>(&self, index: usize, term: &Term<T>) -> Option<Line> {
+        let line = *self.lines.get(index)?;
+        let scrolled = ScrollbackState::new(term).scrolled_since(self.scrollback.as_ref()?)?;
+        let line = line - min(scrolled, term.total_lines()) as i32;
+
+        if line >= term.topmost_line() && line <= term.bottommost_line() {
+            Some(line)
+        } else {
+            None
+        }
+    }
+
+    /// Index of the selected line.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Index of the first line visible in the viewport.
+    pub fn scroll(&self) -> usize {
+        self.scroll
+    }
+
+    /// Add the logical lines of new matches, starting at the top of the scrollback.
+    ///
+    /// The bottommost line stays selected, like the terminal's viewport.
+    fn extend<T, I>(&mut self, lines: I, scrollback: ScrollbackState, term: &Term<T>)
+    where
+        I: IntoIterator<Item = Line>,
+    {
+        // Move previous lines along with the lines scrolled into the history.
+        if let Some(old) = self.scrollback.replace(scrollback) {
+            match scrollback.scrolled_since(&old) {
+                Some(0) => (),
+                Some(scrolled) => {
+                    let scrolled = min(scrolled, term.total_lines()) as i32;
+                    for line in &mut self.lines {
+                        *line -= scrolled;
+                    }
+
+                    let topmost_line = term.topmost_line();
+                    let removed =
+                        self.lines.iter().take_while(|line| **line < topmost_line).count();
+                    self.lines.drain(..removed);
+                    self.selected = self.selected.saturating_sub(removed);
+                    self.scroll = self.scroll.saturating_sub(removed);
+                },
+                None => *self = Self { scrollback: Some(scrollback), ..Self::default() },
+            }
+        }
+
+        let follow_bottom = self.selected + 1 >= self.lines.len();
+
+        // Store each logical line only once, independent of its number of matches.
+        for line in lines {
+            if self.lines.last() != Some(&line) {
+                self.lines.push(line);
+            }
+        }
+
+        if follow_bottom {
+            self.select(self.lines.len(), term.screen_lines());
+        }
+    }
+
+    /// Move the selection, keeping it within the viewport.
+    fn select(&mut self, selected: usize, screen_lines: usize) {
+        self.selected = min(selected, self.lines.len().saturating_sub(1));
+
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + screen_lines {
+            self.scroll = self.selected + 1 - screen_lines;
+        }
+    }
+}
+
+/// Formatter for answering clipboard load requests.
+type ClipboardFormatter = Arc<dyn Fn(&str) -> String + Sync + Send + 'static>;
+
+/// Action waiting for confirmation by the user.
+enum Confirmation {
+    /// Paste text into the terminal.
+    Paste(String),
+
+    /// Store text in the clipboard on behalf of the application.
+    ClipboardStore(ClipboardType, String),
+
+    /// Send the clipboard content to the application.
+    ClipboardLoad(ClipboardType, ClipboardFormatter),
+}
+
+impl Confirmation {
+    /// Check if the action was requested by the application instead of the user.
+    fn requested_by_application(&self) -> bool {
+        matches!(self, Confirmation::ClipboardStore(..) | Confirmation::ClipboardLoad(..))
+    }
+}
+
+/// Response to an application's clipboard request.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Osc52Response {
+    /// Answer the request immediately.
+    Answer,
+
+    /// Ignore the request.
+    Ignore,
+
+    /// Ask the user for confirmation.
+    Ask,
+}
+
+impl Osc52Response {
+    /// Response to a request made while another request might be waiting for confirmation.
+    ///
+    /// Pending requests are never replaced, to make sure the user confirms the visible prompt.
+    fn new(policy: Osc52Policy, confirmation_pending: bool) -> Self {
+        match policy {
+            Osc52Policy::Allow => Osc52Response::Answer,
+            Osc52Policy::Ask if !confirmation_pending => Osc52Response::Ask,
+            Osc52Policy::Ask | Osc52Policy::Deny => Osc52Response::Ignore,
+        }
+    }
+}
+
 /// Regex search state.
 pub struct SearchState {
     /// Search direction.
@@ -112,7 +357,37 @@ pub struct SearchState {
     history_index: Option<usize>,
 
     /// Compiled search automatons.
-    dfas: Option<RegexSearch>,
+    dfas: Option<Arc<RegexSearch>>,
+
+    /// Flags used when compiling the search regex.
+    flags: SearchFlags,
+
+    /// Number of matches for the active search.
+    count: SearchCount,
+
+    /// Start of all counted matches and of the logical lines containing them.
+    match_starts: Vec<(Point, Line)>,
+
+    /// Terminal state while searching for the latest batch of counted matches.
+    match_scrollback: Option<ScrollbackState>,
+
+    /// Identifier of the active counting pass.
+    count_pass: usize,
+
+    /// Cancellation handle of the active counting pass.
+    count_cancel: Option<CancelHandle>,
+
+    /// Time of the first terminal update since matches were last counted.
+    count_invalidated: Option<Instant>,
+
+    /// Filtered view of all matching lines.
+    filter: Option<SearchFilter>,
+
+    /// Cancellation handle of the search regex which is currently being compiled.
+    compiling: Option<CancelHandle>,
+
+    /// Search regex changed while it was being compiled.
+    compile_pending: bool,
 }
 
 impl SearchState {
@@ -133,7 +408,22 @@ impl SearchState {
 
     /// Active search dfas.
     pub fn dfas(&self) -> Option<&RegexSearch> {
-        self.dfas.as_ref()
+        self.dfas.as_deref()
+    }
+
+    /// Flags used for the search regex.
+    pub fn flags(&self) -> SearchFlags {
+        self.flags
+    }
+
+    /// Number of matches for the active search.
+    pub fn count(&self) -> Option<&SearchCount> {
+        self.dfas.as_ref().map(|_| &self.count)
+    }
+
+    /// Filtered view of all matching lines, if active.
+    pub fn filter(&self) -> Option<&SearchFilter> {
+        self.filter.as_ref()
     }
 
     /// Search regex text if a search is active.
@@ -152,6 +442,16 @@ impl Default for SearchState {
             history: Default::default(),
             origin: Default::default(),
             dfas: Default::default(),
+            flags: Default::default(),
+            count: Default::default(),
+            match_starts: Default::default(),
+            match_scrollback: Default::default(),
+            count_pass: Default::default(),
+            count_cancel: Default::default(),
+            count_invalidated: Default::default(),
+            filter: Default::default(),
+            compiling: Default::default(),
+            compile_pending: Default::default(),
         }
     }
 }
@@ -171,12 +471,20 @@ pub struct ActionContext<'a, N, T> {
     pub event_loop: &'a EventLoopWindowTarget<Event>,
     pub scheduler: &'a mut Scheduler,
     pub search_state: &'a mut SearchState,
+    shared_terminal: &'a Arc<FairMutex<Term<T>>>,
+    mark_cursor: &'a mut Option<MarkCursor>,
+    pending_confirmation: &'a mut Option<Confirmation>,
+    macros: &'a mut Macros,
+    foreground_process: &'a mut ForegroundProcess,
+    event_proxy: &'a EventProxy,
     cli_options: &'a CLIOptions,
     font_size: &'a mut Size,
     dirty: &'a mut bool,
 }
 
-impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionContext<'a, N, T> {
+impl<'a, N: Notify + 'a, T: EventListener + Send + 'static> input::ActionContext<T>
+    for ActionContext<'a, N, T>
+{
     #[inline]
     fn write_to_pty<B: Into<Cow<'static, [u8]>>>(&self, val: B) {
         self.notifier.notify(val);
@@ -228,10 +536,45 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
             None => return,
         };
 
+        let drag = self.mouse.selection_press;
         if ty == ClipboardType::Selection && self.config.selection.save_to_clipboard {
-            self.clipboard.store(ClipboardType::Clipboard, text.clone());
+            self.store_dragged(ClipboardType::Clipboard, text.clone(), drag);
+        }
+        self.store_dragged(ty, text, drag);
+    }
+
+    fn copy_selection_formatted(&mut self, format: CopyFormat) {
+        let mut styled = StyledText::default();
+        if self.terminal.write_selection(&mut styled).is_none() {
+            return;
+        }
+        styled.process(self.config.selection.copy);
+
+        // Apply colors changed through escape sequences to the configured palette.
+        let mut colors = self.display.colors;
+        for index in 0..COUNT {
+            if let Some(color) = self.terminal.colors()[index] {
+                colors[index] = color;
+            }
         }
-        self.clipboard.store(ty, text);
+
+        let bold_bright = self.config.ui_config.draw_bold_text_with_bright_colors;
+        let text = styled.format(format, &colors, bold_bright);
+        let plain = self.terminal.selection_to_string().unwrap_or_default();
+        let mime_type = format.mime_type();
+        let record_history = !self.history_excluded();
+        let ty = ClipboardType::Clipboard;
+        self.clipboard.store_with_mime_type(ty, mime_type, text, plain, record_history);
+    }
+
+    fn save_content(&mut self, region: ExportRegion) {
+        let message = match export::export(self.terminal, region, &self.config.ui_config.export) {
+            Ok(path) => Message::new(format!("Saved to {}", path.display()), MessageType::Info),
+            Err(err) => Message::new(format!("Unable to save: {}", err), MessageType::Error),
+        };
+
+        self.message_buffer.push(message);
+        self.display_update_pending.dirty = true;
     }
 
     fn selection_is_empty(&self) -> bool {
@@ -328,6 +671,11 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         &mut self.display
     }
 
+    #[inline]
+    fn key_sequence(&mut self) -> &mut KeySequence {
+        &mut self.display.key_sequence
+    }
+
     #[inline]
     fn terminal(&self) -> &Term<T> {
         self.terminal
@@ -339,47 +687,76 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
     }
 
     fn spawn_new_instance(&mut self) {
-        let mut env_args = env::args();
-        let alacritty = env_args.next().unwrap();
+        self.spawn_instance(&[]);
+    }
 
-        #[cfg(unix)]
-        let mut args = {
-            // Use working directory of controlling process, or fallback to initial shell.
-            let mut pid = unsafe { libc::tcgetpgrp(tty::master_fd()) };
-            if pid < 0 {
-                pid = tty::child_pid();
-            }
+    #[inline]
+    fn mark_mode_active(&self) -> bool {
+        self.mark_cursor.is_some()
+    }
+
+    fn toggle_mark_mode(&mut self) {
+        if self.mark_cursor.take().is_some() {
+            self.clear_selection();
+        } else {
+            // Start selecting from the terminal cursor.
+            let point = self.terminal.grid().cursor.point;
+            *self.mark_cursor = Some(MarkCursor::new(self.terminal, point));
+        }
+        *self.dirty = true;
+    }
 
-            #[cfg(not(any(target_os = "macos", target_os = "freebsd")))]
-            let link_path = format!("/proc/{}/cwd", pid);
-            #[cfg(target_os = "freebsd")]
-            let link_path = format!("/compat/linux/proc/{}/cwd", pid);
-            #[cfg(not(target_os = "macos"))]
-            let cwd = fs::read_link(link_path);
-            #[cfg(target_os = "macos")]
-            let cwd = macos::proc::cwd(pid);
-
-            // Add the current working directory as parameter.
-            cwd.map(|path| vec!["--working-directory".into(), path]).unwrap_or_default()
+    fn mark_motion(&mut self, motion: ViMotion) {
+        let origin = match *self.mark_cursor {
+            Some(mark_cursor) => mark_cursor.point(self.terminal),
+            None => return,
         };
 
-        #[cfg(not(unix))]
-        let mut args: Vec<PathBuf> = Vec::new();
+        let point = ViModeCursor::new(origin).motion(self.terminal, motion).point;
+        *self.mark_cursor = Some(MarkCursor::new(self.terminal, point));
 
-        let working_directory_set = !args.is_empty();
+        // Extend the selection like vi mode, starting a new one if necessary.
+        let selection = self
+            .terminal
+            .selection
+            .get_or_insert_with(|| Selection::new(SelectionType::Simple, origin, Side::Left));
+        selection.update(point, Side::Left);
+        selection.include_all();
 
-        // Reuse the arguments passed to Alacritty for the new instance.
-        while let Some(arg) = env_args.next() {
-            // Drop working directory from existing parameters.
-            if working_directory_set && arg == "--working-directory" {
-                let _ = env_args.next();
-                continue;
-            }
+        self.terminal.scroll_to_point(point);
+        *self.dirty = true;
+    }
 
-            args.push(arg.into());
+    fn mark_select(&mut self, ty: SelectionType) {
+        if let Some(mark_cursor) = *self.mark_cursor {
+            let point = mark_cursor.point(self.terminal);
+            self.start_selection(ty, point, Side::Left);
         }
+    }
 
-        start_daemon(&alacritty, &args);
+    fn open_scrollback(&mut self) {
+        let (path, line) = match export::export_temporary(self.terminal) {
+            Ok(export) => export,
+            Err(err) => {
+                let message = format!("Unable to save scrollback: {}", err);
+                self.message_buffer.push(Message::new(message, MessageType::Error));
+                self.display_update_pending.dirty = true;
+                return;
+            },
+        };
+
+        let viewer = env::var("PAGER")
+            .or_else(|_| env::var("EDITOR"))
+            .ok()
+            .filter(|viewer| !viewer.trim().is_empty())
+            .unwrap_or_else(|| String::from("less"));
+
+        // Most pagers and editors accept `+N` to start at line N.
+        let mut command: Vec<String> = viewer.split_whitespace().map(String::from).collect();
+        command.push(format!("+{}", line));
+        command.push(path.to_string_lossy().into_owned());
+
+        self.spawn_instance(&export::remove_after_exit(command, &path));
     }
 
     fn change_font_size(&mut self, delta: f32) {
@@ -397,6 +774,12 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 
     #[inline]
     fn pop_message(&mut self) {
+        // Dismissing a confirmation prompt cancels the pending action.
+        let target = self.message_buffer.message().and_then(|message| message.target());
+        if target.map(String::as_str) == Some(CONFIRMATION_MESSAGE_TARGET) {
+            *self.pending_confirmation = None;
+        }
+
         if !self.message_buffer.is_empty() {
             self.display_update_pending.dirty = true;
             self.message_buffer.pop();
@@ -406,6 +789,21 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 
     #[inline]
     fn start_search(&mut self, direction: Direction) {
+        // Pick up searches from previous sessions and other instances, without dropping searches
+        // which are still being written.
+        if self.config.ui_config.search.persistent_history {
+            // Keep the current history while another instance is updating the file.
+            match search_history::load(MAX_SEARCH_HISTORY_SIZE) {
+                Ok(history) => search_history::merge(
+                    &mut self.search_state.history,
+                    history,
+                    MAX_SEARCH_HISTORY_SIZE,
+                ),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => (),
+                Err(err) => warn!("Unable to load search history: {}", err),
+            }
+        }
+
         // Only create new history entry if the previous regex wasn't empty.
         if self.search_state.history.get(0).map_or(true, |regex| !regex.is_empty()) {
             self.search_state.history.push_front(String::new());
@@ -435,6 +833,12 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 
     #[inline]
     fn confirm_search(&mut self) {
+        // Leave the filter view, without ending the search.
+        if self.search_state.filter.is_some() {
+            self.confirm_search_filter();
+            return;
+        }
+
         // Just cancel search when not in vi mode.
         if !self.terminal.mode().contains(TermMode::VI) {
             self.cancel_search();
@@ -465,6 +869,12 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 
         self.search_state.dfas = None;
 
+        // Discard the result of any pending regex compilation.
+        if let Some(cancel) = &self.search_state.compiling {
+            cancel.cancel();
+            self.search_state.compile_pending = true;
+        }
+
         self.exit_search();
     }
 
@@ -509,9 +919,34 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         }
     }
 
+    #[inline]
+    fn toggle_search_filter(&mut self) {
+        if self.search_state.filter.take().is_none() {
+            let scrollback = self
+                .search_state
+                .match_scrollback
+                .unwrap_or_else(|| ScrollbackState::new(self.terminal));
+            let lines = self.search_state.match_starts.iter().map(|(_, line)| *line);
+
+            let mut filter = SearchFilter::default();
+            filter.extend(lines, scrollback, self.terminal);
+            self.search_state.filter = Some(filter);
+        }
+
+        *self.dirty = true;
+    }
+
     /// Go to the previous regex in the search history.
     #[inline]
     fn search_history_previous(&mut self) {
+        // Move the selection up while the filter view is active.
+        let screen_lines = self.terminal.screen_lines();
+        if let Some(filter) = &mut self.search_state.filter {
+            filter.select(filter.selected.saturating_sub(1), screen_lines);
+            *self.dirty = true;
+            return;
+        }
+
         let index = match &mut self.search_state.history_index {
             None => return,
             Some(index) if *index + 1 >= self.search_state.history.len() => return,
@@ -525,6 +960,14 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
     /// Go to the previous regex in the search history.
     #[inline]
     fn search_history_next(&mut self) {
+        // Move the selection down while the filter view is active.
+        let screen_lines = self.terminal.screen_lines();
+        if let Some(filter) = &mut self.search_state.filter {
+            filter.select(filter.selected + 1, screen_lines);
+            *self.dirty = true;
+            return;
+        }
+
         let index = match &mut self.search_state.history_index {
             Some(0) | None => return,
             Some(index) => index,
@@ -534,8 +977,28 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         self.update_search();
     }
 
+    #[inline]
+    fn toggle_search_flags(&mut self, flags: SearchFlags) {
+        self.search_state.flags.toggle(flags);
+
+        // Case insensitive and smart case matching are mutually exclusive.
+        let case_flags = SearchFlags::CASE_INSENSITIVE | SearchFlags::SMART_CASE;
+        if self.search_state.flags.contains(case_flags) {
+            self.search_state.flags.remove(case_flags - flags);
+        }
+
+        self.update_search();
+        *self.dirty = true;
+    }
+
     #[inline]
     fn advance_search_origin(&mut self, direction: Direction) {
+        // Leave the filter view, focusing the selected line.
+        if self.search_state.filter.is_some() {
+            self.confirm_search_filter();
+            return;
+        }
+
         // Use focused match as new search origin if available.
         if let Some(focused_match) = &self.search_state.focused_match {
             let new_origin = match direction {
@@ -621,10 +1084,210 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 
     /// Process a new character for keyboard hints.
     fn hint_input(&mut self, c: char) {
-        if let Some(hint) = self.display.hint_state.keyboard_input(self.terminal, c) {
-            self.mouse.block_hint_launcher = false;
-            self.trigger_hint(&hint);
+        match self.display.hint_state.keyboard_input(self.terminal, c) {
+            Some(HintSelection::Single(hint)) => {
+                // Make sure matches from the scrollback history are visible.
+                let viewport_start = Line(-(self.terminal.grid().display_offset() as i32));
+                let viewport_end = viewport_start + self.terminal.bottommost_line();
+                if hint.bounds.end().line < viewport_start
+                    || hint.bounds.start().line > viewport_end
+                {
+                    self.terminal.scroll_to_point(*hint.bounds.start());
+                }
+
+                self.mouse.block_hint_launcher = false;
+                self.trigger_hint(&hint);
+            },
+            Some(HintSelection::Multiple(hints)) => {
+                self.mouse.block_hint_launcher = false;
+                self.trigger_hints(&hints);
+            },
+            None => (),
+        }
+        *self.dirty = true;
+    }
+
+    fn show_clipboard_history(&mut self) {
+        let entries = self.clipboard.history().iter().map(str::to_owned).collect();
+        self.display.picker = Some(Picker::new(PickerItems::ClipboardHistory(entries)));
+        *self.dirty = true;
+    }
+
+    fn paste_history(&mut self, index: usize) {
+        if let Some(text) = self.clipboard.history().get(index).map(str::to_owned) {
+            self.paste(&text);
+        }
+    }
+
+    fn cycle_clipboard_history(&mut self) {
+        let text = match self.clipboard.history_mut().cycle() {
+            Some(text) => text.to_owned(),
+            None => return,
+        };
+
+        let message = format!("Clipboard: {}", clipboard_preview(&text));
+        let mut message = Message::new(message, MessageType::Info);
+        message.set_target(CLIPBOARD_HISTORY_MESSAGE_TARGET.into());
+        self.message_buffer.remove_target(CLIPBOARD_HISTORY_MESSAGE_TARGET);
+        self.message_buffer.push(message);
+        self.display_update_pending.dirty = true;
+
+        self.clipboard.store_without_history(ClipboardType::Clipboard, text);
+    }
+
+    fn record_macro(&mut self, name: String) {
+        // Finish the active recording before starting a new one.
+        self.stop_macro_recording();
+
+        let text = format!("Recording macro {}", name);
+        let mut message = Message::new(text, MessageType::Info);
+        message.set_target(MACRO_MESSAGE_TARGET.into());
+        self.message_buffer.push(message);
+
+        self.macros.start_recording(name);
+    }
+
+    fn stop_macro_recording(&mut self) {
+        self.message_buffer.remove_target(MACRO_MESSAGE_TARGET);
+        self.display_update_pending.dirty = true;
+
+        if let Err(err) = self.macros.stop_recording() {
+            let text = format!("Unable to save macros, keeping them in memory only: {}", err);
+            self.message_buffer.push(Message::new(text, MessageType::Error));
+        }
+    }
+
+    fn play_macro(&mut self, name: &str) {
+        // Prevent macros from recording themselves.
+        if self.macros.recording() == Some(name) {
+            return;
+        }
+
+        let input = match self.macros.get(name) {
+            Some(input) => input.to_owned(),
+            None => {
+                warn!("Unable to play unknown macro {}", name);
+                return;
+            },
+        };
+
+        self.on_typing_start();
+        self.clear_selection();
+        self.scroll(Scroll::Bottom);
+
+        self.record_input(input.as_bytes());
+        self.write_to_pty(input.into_bytes());
+    }
+
+    #[inline]
+    fn foreground_process(&mut self) -> Option<String> {
+        self.foreground_process.name().map(String::from)
+    }
+
+    #[inline]
+    fn record_input(&mut self, bytes: &[u8]) {
+        // Never store passwords in the plain text macro file.
+        if self.macros.recording().is_some() && !self.password_prompt_active() {
+            self.macros.record(&String::from_utf8_lossy(bytes));
+        }
+    }
+
+    fn enter_key_table(&mut self, table: String, locked: bool) {
+        self.display.key_sequence.enter_table(table, locked);
+        self.update_key_sequence_timeout();
+        *self.dirty = true;
+    }
+
+    fn leave_key_table(&mut self) {
+        self.display.key_sequence.reset();
+        self.update_key_sequence_timeout();
+        *self.dirty = true;
+    }
+
+    fn update_key_sequence_timeout(&mut self) {
+        self.scheduler.unschedule(TimerId::KeySequence);
+
+        let timeout = self.config.ui_config.key_sequence_timeout();
+        if let Some(timeout) = timeout.filter(|_| self.display.key_sequence.active()) {
+            let event = Event::KeySequenceTimeout.into();
+            self.scheduler.schedule(event, timeout, false, TimerId::KeySequence);
+        }
+    }
+
+    fn show_character_picker(&mut self) {
+        self.display.picker = Some(Picker::new(PickerItems::Characters));
+        *self.dirty = true;
+    }
+
+    /// Handle keyboard input while the picker overlay is visible.
+    fn picker_input(&mut self, key: VirtualKeyCode) -> bool {
+        let page_lines = self.display.size_info.screen_lines().saturating_sub(1);
+        let picker = match &mut self.display.picker {
+            Some(picker) => picker,
+            None => return false,
+        };
+
+        match key {
+            VirtualKeyCode::Up => picker.select_by(-1, page_lines),
+            VirtualKeyCode::Down => picker.select_by(1, page_lines),
+            VirtualKeyCode::PageUp => picker.select_by(-(page_lines as isize), page_lines),
+            VirtualKeyCode::PageDown => picker.select_by(page_lines as isize, page_lines),
+            VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                let picker = self.display.picker.take();
+                match picker.as_ref().and_then(Picker::selected_item) {
+                    Some(PickerItem::Char(c)) => self.insert_char(c),
+                    Some(PickerItem::Text(text)) => self.paste(text),
+                    None => (),
+                }
+            },
+            VirtualKeyCode::Escape => self.display.picker = None,
+            _ => return false,
+        }
+
+        *self.dirty = true;
+        true
+    }
+
+    fn picker_char(&mut self, c: char) {
+        let picker = match &mut self.display.picker {
+            Some(picker) => picker,
+            None => return,
+        };
+
+        match c {
+            '\x08' | '\x7f' => picker.pop_char(),
+            c if c.is_control() => return,
+            c => picker.push_char(c),
+        }
+
+        *self.dirty = true;
+    }
+
+    /// Handle keyboard input while the hint command output overlay is visible.
+    fn hint_output_input(&mut self, key: VirtualKeyCode) {
+        let page_lines = self.display.size_info.screen_lines().saturating_sub(1);
+        let output = match &mut self.display.hint_output {
+            Some(output) => output,
+            None => return,
+        };
+
+        let total_lines = output.lines().len() as isize;
+        match key {
+            VirtualKeyCode::Up => output.scroll_by(-1, page_lines),
+            VirtualKeyCode::Down => output.scroll_by(1, page_lines),
+            VirtualKeyCode::PageUp => output.scroll_by(-(page_lines as isize), page_lines),
+            VirtualKeyCode::PageDown => output.scroll_by(page_lines as isize, page_lines),
+            VirtualKeyCode::Home => output.scroll_by(-total_lines, page_lines),
+            VirtualKeyCode::End => output.scroll_by(total_lines, page_lines),
+            VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                output.set_visible(false);
+                let text = output.stdout().to_owned();
+                self.paste(&text);
+            },
+            VirtualKeyCode::Escape => output.set_visible(false),
+            _ => return,
         }
+
         *self.dirty = true;
     }
 
@@ -634,49 +1297,265 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
             return;
         }
 
-        match &hint.action {
-            // Launch an external program.
-            HintAction::Command(command) => {
-                let text = self.terminal.bounds_to_string(*hint.bounds.start(), *hint.bounds.end());
-                let mut args = command.args().to_vec();
-                args.push(text);
-                start_daemon(command.program(), &args);
-            },
-            // Copy the text to the clipboard.
-            HintAction::Action(HintInternalAction::Copy) => {
-                let text = self.terminal.bounds_to_string(*hint.bounds.start(), *hint.bounds.end());
-                self.clipboard.store(ClipboardType::Clipboard, text);
+        match &hint.hint.action {
+            // Launch an external program.
+            HintAction::Command(command) => {
+                let text = self.terminal.bounds_to_string(*hint.bounds.start(), *hint.bounds.end());
+                let args = command_args(command.args(), &hint.hint.regex, &text);
+
+                if hint.hint.capture_output {
+                    // Wait for the command's output without blocking the UI.
+                    let program = command.program().to_owned();
+                    let event_proxy = self.event_proxy.clone();
+                    thread::spawn_named("hint command", move || {
+                        let output = run_with_output(
+                            &program,
+                            &args,
+                            HINT_COMMAND_TIMEOUT,
+                            MAX_HINT_OUTPUT_BYTES,
+                        );
+                        let output = HintOutput::new(&program, output);
+                        event_proxy.send_event(Event::HintOutput(Box::new(output)));
+                    });
+                } else {
+                    start_daemon(command.program(), &args);
+                }
+            },
+            // Copy the text to the clipboard.
+            HintAction::Action(HintInternalAction::Copy) => {
+                let text = self.terminal.bounds_to_string(*hint.bounds.start(), *hint.bounds.end());
+                let text = self.terminal.process_copied_text(text);
+                self.store_clipboard(ClipboardType::Clipboard, text);
+            },
+            // Write the text to the PTY/search.
+            HintAction::Action(HintInternalAction::Paste) => {
+                let text = self.terminal.bounds_to_string(*hint.bounds.start(), *hint.bounds.end());
+                self.paste(&text);
+            },
+            // Select the text.
+            HintAction::Action(HintInternalAction::Select) => {
+                self.start_selection(SelectionType::Simple, *hint.bounds.start(), Side::Left);
+                self.update_selection(*hint.bounds.end(), Side::Right);
+                self.copy_selection(ClipboardType::Selection);
+            },
+            // Move the vi mode cursor.
+            HintAction::Action(HintInternalAction::MoveViModeCursor) => {
+                // Enter vi mode if we're not in it already.
+                if !self.terminal.mode().contains(TermMode::VI) {
+                    self.terminal.toggle_vi_mode();
+                }
+
+                self.terminal.vi_goto_point(*hint.bounds.start());
+            },
+        }
+    }
+
+    /// Paste a text into the terminal.
+    fn paste(&mut self, text: &str) {
+        if self.search_active() {
+            for c in text.chars() {
+                self.search_input(c);
+            }
+            return;
+        }
+
+        let config = self.config.ui_config.paste;
+        let text = if config.sanitize { paste::sanitize(text) } else { text.to_owned() };
+
+        if config.confirm && paste::requires_confirmation(&text) {
+            let lines = text.lines().count();
+            let prompt = if lines > 1 {
+                format!("Paste {} lines? Press y to confirm or any other key to cancel", lines)
+            } else {
+                String::from("Paste text with control characters? Press y to confirm")
+            };
+
+            self.request_confirmation(prompt, Confirmation::Paste(text));
+        } else {
+            self.write_paste(&text);
+        }
+    }
+
+    #[inline]
+    fn confirmation_pending(&self) -> bool {
+        self.pending_confirmation.is_some()
+    }
+
+    #[inline]
+    fn explicit_confirmation_required(&self) -> bool {
+        let pending = self.pending_confirmation.as_ref();
+        pending.map(Confirmation::requested_by_application) == Some(true)
+    }
+
+    fn confirm(&mut self, confirmed: bool) {
+        self.message_buffer.remove_target(CONFIRMATION_MESSAGE_TARGET);
+        self.display_update_pending.dirty = true;
+
+        match self.pending_confirmation.take() {
+            Some(_) if !confirmed => (),
+            Some(Confirmation::Paste(text)) => self.write_paste(&text),
+            Some(Confirmation::ClipboardStore(ty, text)) => self.store_clipboard(ty, text),
+            Some(Confirmation::ClipboardLoad(ty, format)) => {
+                let text = format(self.clipboard.load(ty).as_str());
+                self.write_to_pty(text.into_bytes());
+            },
+            None => (),
+        }
+    }
+
+    /// Toggle the vi mode status.
+    #[inline]
+    fn toggle_vi_mode(&mut self) {
+        if !self.terminal.mode().contains(TermMode::VI) {
+            self.clear_selection();
+        }
+
+        self.cancel_search();
+        self.terminal.toggle_vi_mode();
+
+        *self.dirty = true;
+    }
+
+    fn message(&self) -> Option<&Message> {
+        self.message_buffer.message()
+    }
+
+    fn config(&self) -> &Config {
+        self.config
+    }
+
+    fn event_loop(&self) -> &EventLoopWindowTarget<Event> {
+        self.event_loop
+    }
+
+    fn clipboard_mut(&mut self) -> &mut Clipboard {
+        self.clipboard
+    }
+
+    fn scheduler_mut(&mut self) -> &mut Scheduler {
+        self.scheduler
+    }
+}
+
+impl<'a, N: Notify + 'a, T: EventListener + Send + 'static> ActionContext<'a, N, T> {
+    /// Show the captured output of a hint command.
+    ///
+    /// Output with at most a single line is shown in the message bar, everything else uses the
+    /// output overlay.
+    fn show_hint_output(&mut self, mut output: HintOutput) {
+        self.message_buffer.remove_target(HINT_OUTPUT_MESSAGE_TARGET);
+
+        if output.lines().len() <= 1 {
+            let text = match output.lines().first() {
+                Some(line) => format!("{}: {}", output.title(), line),
+                None => output.title().to_owned(),
+            };
+            let ty = if output.success() { MessageType::Info } else { MessageType::Error };
+
+            let mut message = Message::new(text, ty);
+            message.set_target(HINT_OUTPUT_MESSAGE_TARGET.into());
+            self.message_buffer.push(message);
+            self.display_update_pending.dirty = true;
+        } else {
+            output.set_visible(true);
+        }
+
+        self.display.hint_output = Some(output);
+        *self.dirty = true;
+    }
+
+    /// Show a prompt in the message bar and wait for the user's confirmation.
+    ///
+    /// The request is dropped if another confirmation is still pending.
+    fn request_confirmation(&mut self, prompt: String, confirmation: Confirmation) {
+        if self.pending_confirmation.is_some() {
+            debug!("Ignoring request while another confirmation is pending");
+            return;
+        }
+
+        let mut message = Message::new(prompt, MessageType::Warning);
+        message.set_target(CONFIRMATION_MESSAGE_TARGET.into());
+        self.message_buffer.remove_target(CONFIRMATION_MESSAGE_TARGET);
+        self.message_buffer.push(message);
+        self.display_update_pending.dirty = true;
+
+        *self.pending_confirmation = Some(confirmation);
+    }
+
+    /// Handle an application's request to store text in the clipboard.
+    fn osc52_store(&mut self, ty: ClipboardType, text: String) {
+        let config = &self.config.ui_config.osc52;
+        if text.len() > config.max_store_size {
+            warn!("Ignoring OSC 52 store of {} bytes exceeding osc52.max_store_size", text.len());
+            return;
+        }
+
+        match Osc52Response::new(config.permissions(ty).write, self.confirmation_pending()) {
+            Osc52Response::Answer => self.store_clipboard(ty, text),
+            Osc52Response::Ignore => debug!("Denied OSC 52 store to {:?}", ty),
+            Osc52Response::Ask => {
+                let prompt = format!(
+                    "Allow the application to write \"{}\" to the {}? Press Control+Shift+Y to \
+                     allow or any other key to deny",
+                    clipboard_preview(&text),
+                    clipboard_name(ty),
+                );
+                self.request_confirmation(prompt, Confirmation::ClipboardStore(ty, text));
             },
-            // Write the text to the PTY/search.
-            HintAction::Action(HintInternalAction::Paste) => {
-                let text = self.terminal.bounds_to_string(*hint.bounds.start(), *hint.bounds.end());
-                self.paste(&text);
+        }
+    }
+
+    /// Handle an application's request to read the clipboard.
+    fn osc52_load(&mut self, ty: ClipboardType, format: ClipboardFormatter) {
+        let policy = self.config.ui_config.osc52.permissions(ty).read;
+        match Osc52Response::new(policy, self.confirmation_pending()) {
+            Osc52Response::Answer => {
+                let text = format(self.clipboard.load(ty).as_str());
+                self.write_to_pty(text.into_bytes());
             },
-            // Select the text.
-            HintAction::Action(HintInternalAction::Select) => {
-                self.start_selection(SelectionType::Simple, *hint.bounds.start(), Side::Left);
-                self.update_selection(*hint.bounds.end(), Side::Right);
-                self.copy_selection(ClipboardType::Selection);
+            Osc52Response::Ignore => debug!("Denied OSC 52 load from {:?}", ty),
+            Osc52Response::Ask => {
+                let prompt = format!(
+                    "Allow the application to read \"{}\" from the {}? Press Control+Shift+Y to \
+                     allow or any other key to deny",
+                    clipboard_preview(&self.clipboard.load(ty)),
+                    clipboard_name(ty),
+                );
+                self.request_confirmation(prompt, Confirmation::ClipboardLoad(ty, format));
             },
-            // Move the vi mode cursor.
-            HintAction::Action(HintInternalAction::MoveViModeCursor) => {
-                // Enter vi mode if we're not in it already.
-                if !self.terminal.mode().contains(TermMode::VI) {
-                    self.terminal.toggle_vi_mode();
-                }
+        }
+    }
 
-                self.terminal.vi_goto_point(*hint.bounds.start());
-            },
+    /// Store text in the clipboard, keeping it out of the history at password prompts.
+    fn store_clipboard(&mut self, ty: ClipboardType, text: String) {
+        self.store_dragged(ty, text, None);
+    }
+
+    /// Store text of a selection which might still be changed by the mouse press `drag`.
+    fn store_dragged(&mut self, ty: ClipboardType, text: String, drag: Option<usize>) {
+        if self.history_excluded() {
+            self.clipboard.store_without_history(ty, text);
+        } else {
+            self.clipboard.store_dragged(ty, text, drag);
         }
     }
 
-    /// Paste a text into the terminal.
-    fn paste(&mut self, text: &str) {
-        if self.search_active() {
-            for c in text.chars() {
-                self.search_input(c);
-            }
-        } else if self.terminal().mode().contains(TermMode::BRACKETED_PASTE) {
+    /// Check if copied text should be kept out of the clipboard history.
+    fn history_excluded(&self) -> bool {
+        let config = &self.config.ui_config.clipboard_history;
+        config.exclude_password_prompts && self.password_prompt_active()
+    }
+
+    /// Check if the text in front of the cursor looks like a password prompt.
+    fn password_prompt_active(&self) -> bool {
+        let cursor = self.terminal.grid().cursor.point;
+        let start = Point::new(cursor.line, Column(0));
+        clipboard::is_password_prompt(&self.terminal.bounds_to_string(start, cursor))
+    }
+
+    /// Write pasted text to the PTY.
+    fn write_paste(&mut self, text: &str) {
+        if self.terminal.mode().contains(TermMode::BRACKETED_PASTE) {
             self.write_to_pty(&b"\x1b[200~"[..]);
             self.write_to_pty(text.replace("\x1b", "").into_bytes());
             self.write_to_pty(&b"\x1b[201~"[..]);
@@ -691,41 +1570,114 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         }
     }
 
-    /// Toggle the vi mode status.
-    #[inline]
-    fn toggle_vi_mode(&mut self) {
-        if !self.terminal.mode().contains(TermMode::VI) {
-            self.clear_selection();
+    /// Type a character selected in the character picker, as if it was received from the keyboard.
+    fn insert_char(&mut self, c: char) {
+        if self.search_active() {
+            self.search_input(c);
+            return;
         }
 
-        self.cancel_search();
-        self.terminal.toggle_vi_mode();
+        self.on_typing_start();
+        self.scroll(Scroll::Bottom);
+        self.clear_selection();
 
-        *self.dirty = true;
+        let mut bytes = [0; 4];
+        let bytes = c.encode_utf8(&mut bytes).as_bytes().to_vec();
+        self.record_input(&bytes);
+        self.write_to_pty(bytes);
     }
 
-    fn message(&self) -> Option<&Message> {
-        self.message_buffer.message()
-    }
+    /// Paste the escaped path of a file dropped onto the window.
+    fn paste_dropped_file(&mut self, path: &Path) {
+        let config = self.config.ui_config.drag_and_drop;
 
-    fn config(&self) -> &Config {
-        self.config
-    }
+        let relative_path = if config.relative_paths {
+            foreground_process::working_directory()
+                .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf))
+        } else {
+            None
+        };
 
-    fn event_loop(&self) -> &EventLoopWindowTarget<Event> {
-        self.event_loop
+        let path = match &relative_path {
+            Some(relative_path) if relative_path.as_os_str().is_empty() => Path::new("."),
+            Some(relative_path) => relative_path,
+            None => path,
+        };
+
+        let text = paste::escape_path(&path.to_string_lossy(), config.quoting) + " ";
+        self.paste(&text);
     }
 
-    fn clipboard_mut(&mut self) -> &mut Clipboard {
-        self.clipboard
+    /// Spawn a new Alacritty instance, replacing its command if `command` is not empty.
+    fn spawn_instance(&self, command: &[String]) {
+        let mut env_args = env::args();
+        let alacritty = env_args.next().unwrap();
+
+        // Use working directory of controlling process, or fallback to initial shell.
+        let mut args = foreground_process::working_directory()
+            .map(|path| vec!["--working-directory".into(), path])
+            .unwrap_or_default();
+
+        let working_directory_set = !args.is_empty();
+
+        // Reuse the arguments passed to Alacritty for the new instance.
+        while let Some(arg) = env_args.next() {
+            // Drop working directory from existing parameters.
+            if working_directory_set && arg == "--working-directory" {
+                let _ = env_args.next();
+                continue;
+            }
+
+            // Drop the command, since it is always the last parameter.
+            if !command.is_empty() && (arg == "-e" || arg == "--command") {
+                break;
+            }
+
+            args.push(arg.into());
+        }
+
+        if !command.is_empty() {
+            args.push("--command".into());
+            args.extend(command.iter().map(PathBuf::from));
+        }
+
+        start_daemon(&alacritty, &args);
     }
 
-    fn scheduler_mut(&mut self) -> &mut Scheduler {
-        self.scheduler
+    /// Trigger the hint action for all matches selected in multi-select mode.
+    ///
+    /// Commands are launched once per match, while the text of all matches is joined for copying
+    /// and pasting. Other actions only use the last match.
+    fn trigger_hints(&mut self, hints: &[HintMatch]) {
+        let last_hint = match hints.last() {
+            Some(hint) => hint,
+            None => return,
+        };
+
+        let mut texts = Vec::new();
+        for hint in hints {
+            texts.push(self.terminal.bounds_to_string(*hint.bounds.start(), *hint.bounds.end()));
+        }
+
+        let separator = &self.config.ui_config.hints.separator;
+        match &last_hint.hint.action {
+            HintAction::Command(_) => {
+                for hint in hints {
+                    self.trigger_hint(hint);
+                }
+            },
+            HintAction::Action(HintInternalAction::Copy) => {
+                let text = self.terminal.process_copied_text(texts.join(separator));
+                self.store_clipboard(ClipboardType::Clipboard, text);
+            },
+            HintAction::Action(HintInternalAction::Paste) => {
+                let text = texts.join(separator);
+                self.paste(&text);
+            },
+            HintAction::Action(_) => self.trigger_hint(last_hint),
+        }
     }
-}
 
-impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
     fn update_search(&mut self) {
         let regex = match self.search_state.regex() {
             Some(regex) => regex,
@@ -737,19 +1689,91 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
             self.display.window.set_mouse_visible(false);
         }
 
-        if regex.is_empty() {
+        if let Some(cancel) = &self.search_state.compiling {
+            // Stop the outdated compilation, before compiling the new regex.
+            cancel.cancel();
+            self.search_state.compile_pending = true;
+        } else if regex.is_empty() {
             // Stop search if there's nothing to search for.
             self.search_reset_state();
             self.search_state.dfas = None;
+            self.restart_search_count();
         } else {
-            // Create search dfas for the new regex string.
-            self.search_state.dfas = RegexSearch::new(&regex).ok();
+            // Create search dfas for the new regex string without blocking the UI.
+            let regex = regex.clone();
+            let flags = self.search_state.flags;
+            let size_limit = self.config.ui_config.search.dfa_size_limit;
+            let event_proxy = self.event_proxy.clone();
+            let cancel = CancelHandle::default();
+            let thread_cancel = cancel.clone();
+            thread::spawn_named("search regex compiler", move || {
+                let dfas = RegexSearch::cancellable(&regex, flags, size_limit, &thread_cancel);
+                event_proxy.send_event(Event::SearchCompiled(Box::new(dfas)));
+            });
+            self.search_state.compiling = Some(cancel);
+        }
+
+        *self.dirty = true;
+    }
+
+    /// Update the search once the search regex has been compiled.
+    fn search_compiled(&mut self, dfas: Result<RegexSearch, RegexError>) {
+        self.search_state.compiling = None;
+
+        // Compile the latest regex if it was changed in the meantime.
+        if mem::replace(&mut self.search_state.compile_pending, false) {
+            self.update_search();
+            return;
+        }
+
+        self.search_state.dfas = match dfas {
+            Ok(dfas) => Some(Arc::new(dfas)),
+            Err(RegexError::TooComplex) => {
+                let message =
+                    Message::new(String::from("Search regex too complex"), MessageType::Warning);
+                self.message_buffer.push(message);
+                self.display_update_pending.dirty = true;
+                None
+            },
+            // Ignore syntax errors, since they're expected while typing.
+            Err(RegexError::Invalid(_)) | Err(RegexError::Cancelled) => None,
+        };
 
-            // Update search highlighting.
-            self.goto_match(MAX_SEARCH_WHILE_TYPING);
+        // Search might have been confirmed in vi mode while compiling.
+        if self.search_state.history_index.is_none() {
+            return;
         }
 
+        // Update search highlighting.
+        self.restart_search_count();
+        self.goto_match(MAX_SEARCH_WHILE_TYPING);
+
+        *self.dirty = true;
+    }
+
+    /// Close the filter view and jump to the first match in the selected line.
+    fn confirm_search_filter(&mut self) {
+        let filter = match self.search_state.filter.take() {
+            Some(filter) => filter,
+            None => return,
+        };
         *self.dirty = true;
+
+        let line = match filter.line(filter.selected, self.terminal) {
+            Some(line) => line,
+            None => return,
+        };
+
+        // Move origin to the selected line.
+        let origin = Point::new(line, Column(0));
+        self.terminal.scroll_to_point(origin);
+        self.search_state.display_offset_delta = 0;
+        self.search_state.origin = origin;
+
+        // Focus the first match in the selected line.
+        let direction = mem::replace(&mut self.search_state.direction, Direction::Right);
+        self.goto_match(None);
+        self.search_state.direction = direction;
     }
 
     /// Reset terminal to the state before search was started.
@@ -828,11 +1852,146 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
             },
         }
 
+        self.update_search_focus();
+
+        *self.dirty = true;
+    }
+
+    /// Start counting all matches of the active search on a separate thread.
+    ///
+    /// Since the position of every match is cached, this only needs to be done again once the
+    /// regex or the terminal content changed. This also collects the lines shown in the filter
+    /// view.
+    fn restart_search_count(&mut self) {
+        self.stop_search_count();
+        self.search_state.count_invalidated = None;
+
+        self.search_state.count = SearchCount::default();
+        self.search_state.match_starts.clear();
+        self.search_state.match_scrollback = None;
+        if let Some(filter) = &mut self.search_state.filter {
+            *filter = SearchFilter::default();
+        }
+
+        let dfas = match &self.search_state.dfas {
+            Some(dfas) => dfas.clone(),
+            None => return,
+        };
+
+        let terminal = self.shared_terminal.clone();
+        let event_proxy = self.event_proxy.clone();
+        let pass = self.search_state.count_pass;
+        let cancel = CancelHandle::default();
+        let thread_cancel = cancel.clone();
+        thread::spawn_named("search match counter", move || {
+            count_matches(&terminal, &dfas, pass, &thread_cancel, |matches| {
+                event_proxy.send_event(Event::SearchMatches(Box::new(matches)));
+            });
+        });
+        self.search_state.count_cancel = Some(cancel);
+    }
+
+    /// Stop counting search matches, discarding all results of the active counting pass.
+    fn stop_search_count(&mut self) {
+        self.scheduler.unschedule(TimerId::SearchCount);
+
+        if let Some(cancel) = self.search_state.count_cancel.take() {
+            cancel.cancel();
+        }
+        self.search_state.count_pass = self.search_state.count_pass.wrapping_add(1);
+    }
+
+    /// Count the search matches again, once the terminal content stopped changing.
+    ///
+    /// To keep the count from getting stuck while the terminal is updated continuously, matches
+    /// are always counted again after [`SEARCH_COUNT_MAX_DELAY`].
+    fn invalidate_search_count(&mut self) {
+        if self.search_state.dfas.is_none() {
+            return;
+        }
+
+        let now = Instant::now();
+        let invalidated = *self.search_state.count_invalidated.get_or_insert(now);
+        let max_delay = (invalidated + SEARCH_COUNT_MAX_DELAY).saturating_duration_since(now);
+
+        self.scheduler.unschedule(TimerId::SearchCount);
+        self.scheduler.schedule(
+            Event::SearchCount.into(),
+            min(SEARCH_COUNT_DELAY, max_delay),
+            false,
+            TimerId::SearchCount,
+        );
+    }
+
+    /// Add a batch of matches found by the counting thread.
+    fn add_search_matches(&mut self, matches: SearchMatches) {
+        if matches.pass != self.search_state.count_pass {
+            return;
+        }
+
+        // Move previous matches along with the lines scrolled into the history.
+        let scrolled = match self.search_state.match_scrollback.replace(matches.scrollback) {
+            Some(scrollback) => matches.scrollback.scrolled_since(&scrollback),
+            None => Some(0),
+        };
+        let match_starts = &mut self.search_state.match_starts;
+        match scrolled {
+            Some(0) => (),
+            Some(scrolled) => {
+                let topmost_line = self.terminal.topmost_line();
+                let scrolled = min(scrolled, self.terminal.total_lines()) as i32;
+                for (start, line) in match_starts.iter_mut() {
+                    start.line -= scrolled;
+                    *line -= scrolled;
+                }
+                match_starts.retain(|(start, _)| start.line >= topmost_line);
+            },
+            None => match_starts.clear(),
+        }
+
+        // Add the logical lines to the filter view.
+        if let Some(filter) = &mut self.search_state.filter {
+            let lines = matches.starts.iter().map(|(_, line)| *line);
+            filter.extend(lines, matches.scrollback, self.terminal);
+        }
+
+        self.search_state.match_starts.extend(matches.starts);
+        self.search_state.count.total = self.search_state.match_starts.len();
+        self.search_state.count.complete = matches.complete;
+        self.update_search_focus();
+
         *self.dirty = true;
     }
 
+    /// Update the index of the focused match, using the cached match positions.
+    fn update_search_focus(&mut self) {
+        let match_starts = &self.search_state.match_starts;
+        let focused_start = self.search_state.focused_match.as_ref().map(|m| *m.start());
+        self.search_state.count.focused = focused_start
+            .and_then(|focused| {
+                match_starts.binary_search_by_key(&focused, |(start, _)| *start).ok()
+            })
+            .map(|index| index + 1);
+    }
+
     /// Cleanup the search state.
     fn exit_search(&mut self) {
+        self.stop_search_count();
+        self.search_state.match_starts = Vec::new();
+        self.search_state.match_scrollback = None;
+        self.search_state.filter = None;
+
+        // Store the search for future sessions.
+        let regex = self.search_state.regex().filter(|regex| !regex.is_empty());
+        if let Some(regex) = regex.filter(|_| self.config.ui_config.search.persistent_history) {
+            let regex = regex.to_owned();
+            thread::spawn_named("search history writer", move || {
+                if let Err(err) = search_history::push(&regex, MAX_SEARCH_HISTORY_SIZE) {
+                    warn!("Unable to save search history: {}", err);
+                }
+            });
+        }
+
         self.display_update_pending.dirty = true;
         self.search_state.history_index = None;
         *self.dirty = true;
@@ -869,6 +2028,29 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
     }
 }
 
+/// Shorten clipboard content for display in the message bar.
+fn clipboard_preview(text: &str) -> String {
+    let mut preview: String = text
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .take(CLIPBOARD_PREVIEW_LENGTH)
+        .collect();
+
+    if text.chars().count() > CLIPBOARD_PREVIEW_LENGTH {
+        preview.push('…');
+    }
+
+    preview
+}
+
+/// Name of a clipboard type shown to the user.
+fn clipboard_name(ty: ClipboardType) -> &'static str {
+    match ty {
+        ClipboardType::Clipboard => "clipboard",
+        ClipboardType::Selection => "selection buffer",
+    }
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum ClickState {
     None,
@@ -877,6 +2059,30 @@ pub enum ClickState {
     TripleClick,
 }
 
+/// Position of the mark mode cursor.
+///
+/// Since the terminal content keeps scrolling while mark mode is active, the cursor follows the
+/// line it was placed on into the scrollback history.
+#[derive(Copy, Clone, Debug)]
+pub struct MarkCursor {
+    point: Point,
+    scrolled_lines: usize,
+}
+
+impl MarkCursor {
+    fn new<T>(term: &Term<T>, point: Point) -> Self {
+        Self { point, scrolled_lines: term.grid().scrolled_lines() }
+    }
+
+    /// Current position of the cursor, clamped to the terminal's bounds.
+    fn point<T>(&self, term: &Term<T>) -> Point {
+        let scrolled = term.grid().scrolled_lines().wrapping_sub(self.scrolled_lines);
+        let line = self.point.line - min(scrolled, term.total_lines());
+        let line = max(min(line, term.bottommost_line()), term.topmost_line());
+        Point::new(line, min(self.point.column, term.last_column()))
+    }
+}
+
 /// State of the mouse.
 #[derive(Debug)]
 pub struct Mouse {
@@ -892,6 +2098,8 @@ pub struct Mouse {
     pub block_hint_launcher: bool,
     pub hint_highlight_dirty: bool,
     pub inside_text_area: bool,
+    pub press_count: usize,
+    pub selection_press: Option<usize>,
     pub x: usize,
     pub y: usize,
 }
@@ -909,6 +2117,8 @@ impl Default for Mouse {
             hint_highlight_dirty: Default::default(),
             block_hint_launcher: Default::default(),
             inside_text_area: Default::default(),
+            press_count: Default::default(),
+            selection_press: Default::default(),
             lines_scrolled: Default::default(),
             scroll_px: Default::default(),
             x: Default::default(),
@@ -950,6 +2160,10 @@ pub struct Processor<N> {
     font_size: Size,
     event_queue: Vec<GlutinEvent<'static, Event>>,
     search_state: SearchState,
+    mark_cursor: Option<MarkCursor>,
+    pending_confirmation: Option<Confirmation>,
+    macros: Macros,
+    foreground_process: ForegroundProcess,
     cli_options: CLIOptions,
     dirty: bool,
 }
@@ -965,6 +2179,8 @@ impl<N: Notify + OnResize> Processor<N> {
         display: Display,
         cli_options: CLIOptions,
     ) -> Processor<N> {
+        let macros = Macros::load(config.ui_config.config_paths.first().map(PathBuf::as_path));
+
         Processor {
             font_size: config.ui_config.font.size(),
             message_buffer,
@@ -975,6 +2191,10 @@ impl<N: Notify + OnResize> Processor<N> {
             received_count: Default::default(),
             suppress_chars: Default::default(),
             search_state: Default::default(),
+            mark_cursor: Default::default(),
+            pending_confirmation: Default::default(),
+            macros,
+            foreground_process: Default::default(),
             event_queue: Default::default(),
             modifiers: Default::default(),
             mouse: Default::default(),
@@ -1010,9 +2230,13 @@ impl<N: Notify + OnResize> Processor<N> {
     /// Run the event loop.
     pub fn run<T>(&mut self, terminal: Arc<FairMutex<Term<T>>>, mut event_loop: EventLoop<Event>)
     where
-        T: EventListener,
+        T: EventListener + Send + 'static,
     {
         let mut scheduler = Scheduler::new();
+        let event_proxy = EventProxy::new(event_loop.create_proxy());
+
+        // Compile hint regexes without blocking the UI.
+        self.config.ui_config.hints.compile_in_background(event_proxy.hint_compile_notifier());
 
         // Start the initial cursor blinking timer.
         if self.config.cursor.style().blinking {
@@ -1025,6 +2249,7 @@ impl<N: Notify + OnResize> Processor<N> {
         let mut clipboard = unsafe { Clipboard::new(event_loop.wayland_display()) };
         #[cfg(any(not(feature = "wayland"), target_os = "macos", windows))]
         let mut clipboard = Clipboard::new();
+        clipboard.history_mut().set_max_size(self.config.ui_config.clipboard_history.size);
 
         event_loop.run_return(|event, event_loop, control_flow| {
             if self.config.ui_config.debug.print_events {
@@ -1072,6 +2297,7 @@ impl<N: Notify + OnResize> Processor<N> {
                 },
             }
 
+            let shared_terminal = &terminal;
             let mut terminal = terminal.lock();
 
             let mut display_update_pending = DisplayUpdate::default();
@@ -1092,6 +2318,12 @@ impl<N: Notify + OnResize> Processor<N> {
                 config: &mut self.config,
                 scheduler: &mut scheduler,
                 search_state: &mut self.search_state,
+                shared_terminal,
+                mark_cursor: &mut self.mark_cursor,
+                pending_confirmation: &mut self.pending_confirmation,
+                macros: &mut self.macros,
+                foreground_process: &mut self.foreground_process,
+                event_proxy: &event_proxy,
                 cli_options: &self.cli_options,
                 dirty: &mut self.dirty,
                 event_loop,
@@ -1135,7 +2367,14 @@ impl<N: Notify + OnResize> Processor<N> {
                 }
 
                 // Redraw screen.
-                self.display.draw(terminal, &self.message_buffer, &self.config, &self.search_state);
+                let mark_point = self.mark_cursor.map(|mark_cursor| mark_cursor.point(&terminal));
+                self.display.draw(
+                    terminal,
+                    &self.message_buffer,
+                    &self.config,
+                    &self.search_state,
+                    mark_point,
+                );
             }
         });
 
@@ -1152,7 +2391,7 @@ impl<N: Notify + OnResize> Processor<N> {
         event: GlutinEvent<'_, Event>,
         processor: &mut input::Processor<T, ActionContext<'_, N, T>>,
     ) where
-        T: EventListener,
+        T: EventListener + Send + 'static,
     {
         match event {
             GlutinEvent::UserEvent(event) => match event {
@@ -1175,12 +2414,25 @@ impl<N: Notify + OnResize> Processor<N> {
                     *processor.ctx.dirty = true;
                 },
                 Event::SearchNext => processor.ctx.goto_match(None),
+                Event::SearchCount => processor.ctx.restart_search_count(),
+                Event::SearchMatches(matches) => processor.ctx.add_search_matches(*matches),
+                Event::SearchCompiled(dfas) => processor.ctx.search_compiled(*dfas),
+                Event::HintRegexCompiled => {
+                    processor.ctx.display.hint_state.invalidate_matches();
+                    processor.ctx.mouse.hint_highlight_dirty = true;
+                    *processor.ctx.dirty = true;
+                },
+                Event::HintOutput(output) => processor.ctx.show_hint_output(*output),
                 Event::ConfigReload(path) => Self::reload_config(&path, processor),
                 Event::Scroll(scroll) => processor.ctx.scroll(scroll),
                 Event::BlinkCursor => {
                     processor.ctx.display.cursor_hidden ^= true;
                     *processor.ctx.dirty = true;
                 },
+                Event::KeySequenceTimeout => {
+                    processor.ctx.display.key_sequence.reset();
+                    *processor.ctx.dirty = true;
+                },
                 Event::TerminalEvent(event) => match event {
                     TerminalEvent::Title(title) => {
                         let ui_config = &processor.ctx.config.ui_config;
@@ -1194,7 +2446,12 @@ impl<N: Notify + OnResize> Processor<N> {
                             processor.ctx.display.window.set_title(&ui_config.window.title);
                         }
                     },
-                    TerminalEvent::Wakeup => *processor.ctx.dirty = true,
+                    TerminalEvent::Wakeup => {
+                        processor.ctx.display.hint_state.invalidate_matches();
+                        processor.ctx.invalidate_search_count();
+                        processor.ctx.foreground_process.invalidate();
+                        *processor.ctx.dirty = true;
+                    },
                     TerminalEvent::Bell => {
                         // Set window urgency.
                         if processor.ctx.terminal.mode().contains(TermMode::URGENCY_HINTS) {
@@ -1211,11 +2468,10 @@ impl<N: Notify + OnResize> Processor<N> {
                         }
                     },
                     TerminalEvent::ClipboardStore(clipboard_type, content) => {
-                        processor.ctx.clipboard.store(clipboard_type, content);
+                        processor.ctx.osc52_store(clipboard_type, content);
                     },
                     TerminalEvent::ClipboardLoad(clipboard_type, format) => {
-                        let text = format(processor.ctx.clipboard.load(clipboard_type).as_str());
-                        processor.ctx.write_to_pty(text.into_bytes());
+                        processor.ctx.osc52_load(clipboard_type, format);
                     },
                     TerminalEvent::ColorRequest(index, format) => {
                         let text = format(processor.ctx.display.colors[index]);
@@ -1280,10 +2536,7 @@ impl<N: Notify + OnResize> Processor<N> {
                             processor.on_focus_change(is_focused);
                         }
                     },
-                    WindowEvent::DroppedFile(path) => {
-                        let path: String = path.to_string_lossy().into();
-                        processor.ctx.write_to_pty((path + " ").into_bytes());
-                    },
+                    WindowEvent::DroppedFile(path) => processor.ctx.paste_dropped_file(&path),
                     WindowEvent::CursorLeft { .. } => {
                         processor.ctx.mouse.inside_text_area = false;
 
@@ -1340,7 +2593,7 @@ impl<N: Notify + OnResize> Processor<N> {
     /// Reload the configuration files from disk.
     fn reload_config<T>(path: &Path, processor: &mut input::Processor<T, ActionContext<'_, N, T>>)
     where
-        T: EventListener,
+        T: EventListener + Send + 'static,
     {
         if !processor.ctx.message_buffer.is_empty() {
             processor.ctx.message_buffer.remove_target(LOG_TARGET_CONFIG);
@@ -1354,6 +2607,7 @@ impl<N: Notify + OnResize> Processor<N> {
 
         processor.ctx.display.update_config(&config);
         processor.ctx.terminal.update_config(&config);
+        processor.ctx.clipboard.history_mut().set_max_size(config.ui_config.clipboard_history.size);
 
         // Reload cursor if its thickness has changed.
         if (processor.ctx.config.cursor.thickness() - config.cursor.thickness()).abs()
@@ -1403,6 +2657,10 @@ impl<N: Notify + OnResize> Processor<N> {
         // Update hint keys.
         processor.ctx.display.hint_state.update_alphabet(config.ui_config.hints.alphabet());
 
+        // Compile hint regexes without blocking the UI.
+        let notify = processor.ctx.event_proxy.hint_compile_notifier();
+        config.ui_config.hints.compile_in_background(notify);
+
         *processor.ctx.config = config;
 
         // Update cursor blinking.
@@ -1477,6 +2735,78 @@ impl<N: Notify + OnResize> Processor<N> {
     }
 }
 
+/// Find all matches in the terminal, passing their positions to `send` in batches.
+///
+/// The terminal is only locked while searching a single batch of lines, to avoid blocking the
+/// event loop and the PTY reader for big scrollback buffers.
+fn count_matches<T>(
+    terminal: &FairMutex<Term<T>>,
+    dfas: &RegexSearch,
+    pass: usize,
+    cancel: &CancelHandle,
+    mut send: impl FnMut(SearchMatches),
+) {
+    let mut next_line = None;
+    let mut total = 0;
+    let mut scrollback = None;
+
+    while !cancel.is_cancelled() {
+        let terminal = terminal.lock();
+
+        // Follow the lines scrolled into the history since the last batch, or start over if
+        // previous batches might not match the terminal content anymore.
+        let new_scrollback = ScrollbackState::new(&terminal);
+        let scrolled = match scrollback.replace(new_scrollback) {
+            Some(scrollback) => new_scrollback.scrolled_since(&scrollback),
+            None => Some(0),
+        };
+        match scrolled {
+            Some(scrolled) => {
+                let scrolled = min(scrolled, terminal.total_lines()) as i32;
+                next_line = next_line.map(|line| line - scrolled);
+            },
+            None => {
+                next_line = None;
+                total = 0;
+            },
+        }
+
+        let start_line = match next_line {
+            Some(line) => Line::grid_clamp(line, &*terminal, Boundary::Grid),
+            None => terminal.topmost_line(),
+        };
+
+        // Always end the batch at the end of a line, to prevent splitting matches.
+        let bottommost_line = terminal.bottommost_line();
+        let end_line = min(start_line + SEARCH_COUNT_LINES, bottommost_line);
+        let start = Point::new(start_line, Column(0));
+        let end = terminal.line_search_right(Point::new(end_line, Column(0)));
+
+        let starts: Vec<_> = RegexIter::new(start, end, Direction::Right, &terminal, dfas)
+            .take(MAX_SEARCH_COUNT - total)
+            .map(|regex_match| {
+                let start = *regex_match.start();
+                (start, terminal.line_search_left(start).line)
+            })
+            .collect();
+
+        drop(terminal);
+
+        // Stop counting once the limit is reached.
+        total += starts.len();
+        let complete = end.line >= bottommost_line && total < MAX_SEARCH_COUNT;
+        let done = end.line >= bottommost_line || total >= MAX_SEARCH_COUNT;
+
+        send(SearchMatches { pass, starts, scrollback: new_scrollback, complete });
+
+        if done {
+            break;
+        }
+
+        next_line = Some(end.line + 1);
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct EventProxy(EventLoopProxy<Event>);
 
@@ -1489,6 +2819,12 @@ impl EventProxy {
     pub fn send_event(&self, event: Event) {
         let _ = self.0.send_event(event);
     }
+
+    /// Callback notifying the event loop about a finished hint regex compilation.
+    fn hint_compile_notifier(&self) -> impl Fn() + Clone + Send + 'static {
+        let event_proxy = self.clone();
+        move || event_proxy.send_event(Event::HintRegexCompiled)
+    }
 }
 
 impl EventListener for EventProxy {
@@ -1496,3 +2832,140 @@ impl EventListener for EventProxy {
         let _ = self.0.send_event(Event::TerminalEvent(event));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alacritty_terminal::ansi::{ClearMode, Handler};
+
+    struct MockEventProxy;
+    impl EventListener for MockEventProxy {}
+
+    #[test]
+    fn osc52_responses() {
+        assert_eq!(Osc52Response::new(Osc52Policy::Allow, false), Osc52Response::Answer);
+        assert_eq!(Osc52Response::new(Osc52Policy::Deny, false), Osc52Response::Ignore);
+        assert_eq!(Osc52Response::new(Osc52Policy::Ask, false), Osc52Response::Ask);
+
+        // Pending prompts are never replaced.
+        assert_eq!(Osc52Response::new(Osc52Policy::Allow, true), Osc52Response::Answer);
+        assert_eq!(Osc52Response::new(Osc52Policy::Ask, true), Osc52Response::Ignore);
+    }
+
+    #[test]
+    fn search_filter_follows_new_matches() {
+        let size = SizeInfo::new(10.0, 1.0, 1.0, 1.0, 0.0, 0.0, false);
+        let mut config = Config::default();
+        config.scrolling.set_history(10);
+        let mut term = Term::new(&config, size, MockEventProxy);
+        for _ in 0..6 {
+            term.scroll_up(1);
+        }
+
+        let scrollback = ScrollbackState::new(&term);
+        let mut filter = SearchFilter::default();
+        filter.extend(vec![Line(-6), Line(-6), Line(-3)], scrollback, &term);
+        assert_eq!(filter.lines, &[Line(-6), Line(-3)]);
+        assert_eq!((filter.selected(), filter.scroll()), (1, 1));
+
+        // Keep the selection once the user moved it away from the bottom.
+        filter.select(0, 1);
+        filter.extend(vec![Line(-1)], scrollback, &term);
+        assert_eq!(filter.lines, &[Line(-6), Line(-3), Line(-1)]);
+        assert_eq!((filter.selected(), filter.scroll()), (0, 0));
+    }
+
+    #[test]
+    fn search_filter_follows_scrolling() {
+        let size = SizeInfo::new(10.0, 1.0, 1.0, 1.0, 0.0, 0.0, false);
+        let mut config = Config::default();
+        config.scrolling.set_history(10);
+        let mut term = Term::new(&config, size, MockEventProxy);
+        for _ in 0..6 {
+            term.scroll_up(1);
+        }
+
+        let mut filter = SearchFilter::default();
+        let lines = vec![Line(-6), Line(-3), Line(-1)];
+        filter.extend(lines, ScrollbackState::new(&term), &term);
+
+        // Lines removed from the top of the history are not part of the filter anymore.
+        for _ in 0..6 {
+            term.scroll_up(1);
+        }
+        let lines: Vec<_> = (0..filter.len()).map(|i| filter.line(i, &term)).collect();
+        assert_eq!(lines, vec![None, Some(Line(-9)), Some(Line(-7))]);
+
+        filter.extend(None, ScrollbackState::new(&term), &term);
+        assert_eq!(filter.lines, &[Line(-9), Line(-7)]);
+
+        // Lines are invalidated once the history is modified.
+        term.clear_screen(ClearMode::Saved);
+        assert_eq!(filter.line(0, &term), None);
+    }
+
+    #[test]
+    fn mark_cursor_follows_scrolling() {
+        let size = SizeInfo::new(10.0, 5.0, 1.0, 1.0, 0.0, 0.0, false);
+        let mut config = Config::default();
+        config.scrolling.set_history(3);
+        let mut term = Term::new(&config, size, MockEventProxy);
+
+        let mark_cursor = MarkCursor::new(&term, Point::new(Line(2), Column(3)));
+        term.scroll_up(2);
+        assert_eq!(mark_cursor.point(&term), Point::new(Line(0), Column(3)));
+
+        // Lines removed from the top of the history clamp the cursor to the topmost line.
+        term.scroll_up(10);
+        assert_eq!(mark_cursor.point(&term), Point::new(term.topmost_line(), Column(3)));
+    }
+
+    #[test]
+    fn search_count_follows_scrolling() {
+        let size = SizeInfo::new(10.0, 5.0, 1.0, 1.0, 0.0, 0.0, false);
+        let mut config = Config::default();
+        config.scrolling.set_history(SEARCH_COUNT_LINES as u32 * 2);
+        let mut term = Term::new(&config, size, MockEventProxy);
+        for _ in 0..SEARCH_COUNT_LINES + 10 {
+            term.input('x');
+            term.carriage_return();
+            term.linefeed();
+        }
+
+        let terminal = FairMutex::new(term);
+        let dfas = RegexSearch::new("x").unwrap();
+        let mut lines = Vec::new();
+        let mut scrollback = None;
+        count_matches(&terminal, &dfas, 0, &CancelHandle::default(), |matches| {
+            // Scroll new lines into the history while counting.
+            if lines.is_empty() {
+                terminal.lock().scroll_up(3);
+            }
+
+            let scrolled = match scrollback.replace(matches.scrollback) {
+                Some(scrollback) => matches.scrollback.scrolled_since(&scrollback).unwrap(),
+                None => 0,
+            } as i32;
+            for line in lines.iter_mut() {
+                *line -= scrolled;
+            }
+            lines.extend(matches.starts.iter().map(|(start, _)| start.line));
+        });
+
+        let term = terminal.lock();
+        let expected: Vec<_> =
+            (term.topmost_line().0..term.bottommost_line().0 - 3).map(Line).collect();
+        assert_eq!(lines, expected);
+    }
+
+    #[test]
+    fn application_requests_need_explicit_confirmation() {
+        let format: ClipboardFormatter = Arc::new(|text| text.to_owned());
+        let load = Confirmation::ClipboardLoad(ClipboardType::Clipboard, format);
+        let store = Confirmation::ClipboardStore(ClipboardType::Selection, String::new());
+        assert!(load.requested_by_application());
+        assert!(store.requested_by_application());
+        assert!(!Confirmation::Paste(String::new()).requested_by_application());
+    }
+}
@@ -0,0 +1,258 @@
+//! Saving terminal content to a file.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, process};
+
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Column, Line, Point};
+use alacritty_terminal::term::Term;
+
+use crate::config::export::ExportConfig;
+use crate::copy_format::StyledText;
+
+/// Maximum number suffixed to the file name when the export target already exists.
+const MAX_PATH_SUFFIX: usize = 999;
+
+/// Part of the terminal content which should be saved.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExportRegion {
+    /// Entire scrollback history, including the screen.
+    History,
+
+    /// Lines currently visible in the viewport.
+    Screen,
+}
+
+/// Write the terminal content to the path configured in the template.
+///
+/// Existing files are never overwritten, instead a numeric suffix is added to the file name.
+/// Returns the path of the written file.
+pub fn export<T>(
+    term: &Term<T>,
+    region: ExportRegion,
+    config: &ExportConfig,
+) -> io::Result<PathBuf> {
+    let (start_line, end_line) = match region {
+        ExportRegion::History => (term.topmost_line(), term.bottommost_line()),
+        ExportRegion::Screen => {
+            let top = Line(-(term.grid().display_offset() as i32));
+            (top, top + (term.screen_lines() - 1))
+        },
+    };
+    let start = Point::new(start_line, Column(0));
+    let end = Point::new(end_line, term.last_column());
+
+    let mut content = if config.escapes {
+        let mut styled = StyledText::default();
+        term.write_bounds(&mut styled, start, end);
+        styled.to_ansi()
+    } else {
+        term.bounds_to_string(start, end)
+    };
+
+    strip_trailing_lines(&mut content);
+
+    let path = expand_path(&config.path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let (mut file, path) = create_unique(&path)?;
+    file.write_all(content.as_bytes())?;
+
+    Ok(path)
+}
+
+/// Write the scrollback history to a new temporary file.
+///
+/// Returns the path of the file and the line number of the topmost line in the viewport. The
+/// file is readable only by the current user and should be removed with [`remove_after_exit`].
+pub fn export_temporary<T>(term: &Term<T>) -> io::Result<(PathBuf, usize)> {
+    let viewport_top = Line(-(term.grid().display_offset() as i32));
+
+    let mut content = String::new();
+    if viewport_top > term.topmost_line() {
+        let start = Point::new(term.topmost_line(), Column(0));
+        let end = Point::new(viewport_top - 1i32, term.last_column());
+        content = term.bounds_to_string(start, end);
+    }
+
+    // Line breaks are only missing for lines wrapping into the viewport.
+    let line = content.matches('\n').count() + 1;
+
+    let start = Point::new(viewport_top, Column(0));
+    let end = Point::new(term.bottommost_line(), term.last_column());
+    content += &term.bounds_to_string(start, end);
+    strip_trailing_lines(&mut content);
+
+    let file_name = format!("alacritty-scrollback-{}-{}.txt", process::id(), timestamp());
+    let path = env::temp_dir().join(file_name);
+
+    // Prevent other users from reading the terminal content.
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    options.open(&path)?.write_all(content.as_bytes())?;
+
+    Ok((path, line))
+}
+
+/// Wrap the command viewing a temporary export, to remove the file once the command exits.
+#[cfg(not(windows))]
+pub fn remove_after_exit(command: Vec<String>, path: &Path) -> Vec<String> {
+    // Remove the file even when the window is closed while the command is still running.
+    let script = "trap 'rm -f -- \"$0\"' EXIT HUP TERM; \"$@\"";
+    let path = path.to_string_lossy().into_owned();
+
+    let mut wrapped = vec![String::from("sh"), String::from("-c"), String::from(script), path];
+    wrapped.extend(command);
+    wrapped
+}
+
+/// Wrap the command viewing a temporary export, to remove the file once the command exits.
+#[cfg(windows)]
+pub fn remove_after_exit(command: Vec<String>, path: &Path) -> Vec<String> {
+    let path = path.to_string_lossy().into_owned();
+
+    let mut wrapped = vec![String::from("cmd"), String::from("/C")];
+    wrapped.extend(command);
+    wrapped.extend(vec![String::from("&"), String::from("del"), String::from("/Q"), path]);
+    wrapped
+}
+
+/// Create a new file, adding a `-N` suffix to the file name if the path is taken already.
+fn create_unique(path: &Path) -> io::Result<(File, PathBuf)> {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = path.extension().map(|extension| extension.to_string_lossy().into_owned());
+
+    let mut candidate = path.to_path_buf();
+    for suffix in 1..=MAX_PATH_SUFFIX {
+        match OpenOptions::new().write(true).create_new(true).open(&candidate) {
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => (),
+            result => return result.map(|file| (file, candidate)),
+        }
+
+        let mut file_name = format!("{}-{}", stem, suffix);
+        if let Some(extension) = &extension {
+            file_name.push('.');
+            file_name.push_str(extension);
+        }
+        candidate.set_file_name(file_name);
+    }
+
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", path.display())))
+}
+
+/// Strip blank lines below the last output.
+fn strip_trailing_lines(content: &mut String) {
+    let len = content.trim_end().len();
+    content.truncate(len);
+    content.push('\n');
+}
+
+/// Current UNIX time in seconds.
+fn timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs())
+}
+
+/// Expand the home directory and `{timestamp}` placeholder in a path template.
+fn expand_path(template: &str) -> PathBuf {
+    let mut path = PathBuf::from(template.replace("{timestamp}", &timestamp().to_string()));
+
+    // Resolve paths relative to user's home directory.
+    if let (Ok(stripped), Some(home_dir)) = (path.strip_prefix("~/"), dirs::home_dir()) {
+        path = home_dir.join(stripped);
+    }
+
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alacritty_terminal::ansi::Handler;
+    use alacritty_terminal::event::EventListener;
+    use alacritty_terminal::term::SizeInfo;
+
+    use crate::config::Config;
+
+    struct MockEventProxy;
+    impl EventListener for MockEventProxy {}
+
+    /// Create a terminal with 5 columns and 4 lines, which received the text.
+    fn term_with_text(text: &str) -> Term<MockEventProxy> {
+        let size = SizeInfo::new(5., 4., 1., 1., 0., 0., false);
+        let mut term = Term::new(&Config::default(), size, MockEventProxy);
+        for c in text.chars() {
+            if c == '\n' {
+                term.carriage_return();
+                term.linefeed();
+            } else {
+                term.input(c);
+            }
+        }
+        term
+    }
+
+    /// Create an empty directory for the exports of a test.
+    fn export_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("alacritty-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn export_joins_wrapped_lines() {
+        let term = term_with_text("abcdefg\nhi  ");
+        let dir = export_dir("export-wrapped");
+        let config =
+            ExportConfig { path: dir.join("screen.txt").display().to_string(), escapes: false };
+
+        let path = export(&term, ExportRegion::Screen, &config).unwrap();
+
+        // Trailing blanks and the empty lines below the output are stripped.
+        assert_eq!(fs::read_to_string(path).unwrap(), "abcdefg\nhi\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn export_does_not_overwrite() {
+        let dir = export_dir("export-existing");
+        let config =
+            ExportConfig { path: dir.join("screen.txt").display().to_string(), escapes: false };
+
+        let first = export(&term_with_text("first"), ExportRegion::Screen, &config).unwrap();
+        let second = export(&term_with_text("second"), ExportRegion::Screen, &config).unwrap();
+
+        assert_eq!(first, dir.join("screen.txt"));
+        assert_eq!(second, dir.join("screen-1.txt"));
+        assert_eq!(fs::read_to_string(first).unwrap(), "first\n");
+        assert_eq!(fs::read_to_string(second).unwrap(), "second\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn temporary_export_removed_after_exit() {
+        let path = env::temp_dir().join(format!("alacritty-export-test-{}", process::id()));
+        fs::write(&path, "content").unwrap();
+
+        let command =
+            remove_after_exit(vec![String::from("cat"), path.display().to_string()], &path);
+        let status = process::Command::new(&command[0])
+            .args(&command[1..])
+            .stdout(process::Stdio::null())
+            .status()
+            .unwrap();
+
+        assert!(status.success());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,100 @@
+//! Lookup of the terminal's foreground process.
+
+#[cfg(not(any(target_os = "macos", windows)))]
+use std::fs;
+use std::path::PathBuf;
+
+#[cfg(not(windows))]
+use alacritty_terminal::tty;
+
+#[cfg(target_os = "macos")]
+use crate::macos;
+
+/// Cached name of the terminal's foreground process.
+#[derive(Default, Debug)]
+pub struct ForegroundProcess {
+    /// Process group ID the name was looked up for.
+    #[cfg(not(windows))]
+    pid: Option<libc::pid_t>,
+
+    /// Name of the foreground process.
+    #[cfg(not(windows))]
+    name: Option<String>,
+}
+
+impl ForegroundProcess {
+    /// Name of the foreground process.
+    ///
+    /// The name is only looked up again when the foreground process group has changed, or the
+    /// cache was invalidated since the last lookup.
+    #[cfg(not(windows))]
+    pub fn name(&mut self) -> Option<&str> {
+        let pid = tty::foreground_pid();
+        if self.pid != Some(pid) {
+            self.pid = Some(pid);
+            self.name = process_name(pid);
+        }
+
+        self.name.as_deref()
+    }
+
+    /// Look up the name again on the next access.
+    ///
+    /// Since the process group stays the same when its leader calls `exec`, this should be
+    /// called whenever the PTY has written new output.
+    #[cfg(not(windows))]
+    pub fn invalidate(&mut self) {
+        self.pid = None;
+    }
+
+    /// Name of the foreground process.
+    #[cfg(windows)]
+    pub fn name(&mut self) -> Option<&str> {
+        None
+    }
+
+    /// Look up the name again on the next access.
+    #[cfg(windows)]
+    pub fn invalidate(&mut self) {}
+}
+
+/// Working directory of the terminal's foreground process.
+#[cfg(not(windows))]
+pub fn working_directory() -> Option<PathBuf> {
+    let pid = tty::foreground_pid();
+
+    #[cfg(not(any(target_os = "macos", target_os = "freebsd")))]
+    let link_path = format!("/proc/{}/cwd", pid);
+    #[cfg(target_os = "freebsd")]
+    let link_path = format!("/compat/linux/proc/{}/cwd", pid);
+    #[cfg(not(target_os = "macos"))]
+    let cwd = fs::read_link(link_path);
+    #[cfg(target_os = "macos")]
+    let cwd = macos::proc::cwd(pid);
+
+    cwd.ok()
+}
+
+/// Working directory of the terminal's foreground process.
+#[cfg(windows)]
+pub fn working_directory() -> Option<PathBuf> {
+    None
+}
+
+/// Look up the name of a process.
+#[cfg(not(any(target_os = "macos", windows)))]
+fn process_name(pid: libc::pid_t) -> Option<String> {
+    #[cfg(not(target_os = "freebsd"))]
+    let comm_path = format!("/proc/{}/comm", pid);
+    #[cfg(target_os = "freebsd")]
+    let comm_path = format!("/compat/linux/proc/{}/comm", pid);
+
+    let name = fs::read_to_string(comm_path).ok()?;
+    Some(name.trim_end().to_owned())
+}
+
+/// Look up the name of a process.
+#[cfg(target_os = "macos")]
+fn process_name(pid: libc::pid_t) -> Option<String> {
+    macos::proc::name(pid).ok()
+}
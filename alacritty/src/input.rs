@@ -13,6 +13,7 @@ use std::time::{Duration, Instant};
 use glutin::dpi::PhysicalPosition;
 use glutin::event::{
     ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta, TouchPhase,
+    VirtualKeyCode,
 };
 use glutin::event_loop::EventLoopWindowTarget;
 #[cfg(target_os = "macos")]
@@ -24,17 +25,22 @@ use alacritty_terminal::event::EventListener;
 use alacritty_terminal::grid::{Dimensions, Scroll};
 use alacritty_terminal::index::{Boundary, Column, Direction, Point, Side};
 use alacritty_terminal::selection::SelectionType;
-use alacritty_terminal::term::search::Match;
+use alacritty_terminal::term::search::{Match, SearchFlags};
 use alacritty_terminal::term::{ClipboardType, SizeInfo, Term, TermMode};
 use alacritty_terminal::vi_mode::ViMotion;
 
 use crate::clipboard::Clipboard;
-use crate::config::{Action, BindingMode, Config, Key, SearchAction, ViAction};
+use crate::config::{
+    Action, Binding, BindingMode, Config, Key, MarkAction, SearchAction, ViAction,
+};
+use crate::copy_format::CopyFormat;
 use crate::daemon::start_daemon;
 use crate::display::hint::HintMatch;
+use crate::display::key_sequence::KeySequence;
 use crate::display::window::Window;
 use crate::display::Display;
 use crate::event::{ClickState, Event, Mouse, TYPING_SEARCH_DELAY};
+use crate::export::ExportRegion;
 use crate::message_bar::{self, Message};
 use crate::scheduler::{Scheduler, TimerId};
 
@@ -64,6 +70,9 @@ pub trait ActionContext<T: EventListener> {
     fn mark_dirty(&mut self) {}
     fn size_info(&self) -> SizeInfo;
     fn copy_selection(&mut self, _ty: ClipboardType) {}
+    fn copy_selection_formatted(&mut self, _format: CopyFormat) {}
+    fn save_content(&mut self, _region: ExportRegion) {}
+    fn open_scrollback(&mut self) {}
     fn start_selection(&mut self, _ty: SelectionType, _point: Point, _side: Side) {}
     fn toggle_selection(&mut self, _ty: SelectionType, _point: Point, _side: Side) {}
     fn update_selection(&mut self, _point: Point, _side: Side) {}
@@ -77,6 +86,7 @@ pub trait ActionContext<T: EventListener> {
     fn scroll(&mut self, _scroll: Scroll) {}
     fn window(&mut self) -> &mut Window;
     fn display(&mut self) -> &mut Display;
+    fn key_sequence(&mut self) -> &mut KeySequence;
     fn terminal(&self) -> &Term<T>;
     fn terminal_mut(&mut self) -> &mut Term<T>;
     fn spawn_new_instance(&mut self) {}
@@ -98,13 +108,47 @@ pub trait ActionContext<T: EventListener> {
     fn search_history_next(&mut self) {}
     fn search_next(&mut self, origin: Point, direction: Direction, side: Side) -> Option<Match>;
     fn advance_search_origin(&mut self, _direction: Direction) {}
+    fn toggle_search_flags(&mut self, _flags: SearchFlags) {}
+    fn toggle_search_filter(&mut self) {}
     fn search_direction(&self) -> Direction;
     fn search_active(&self) -> bool;
     fn on_typing_start(&mut self) {}
     fn toggle_vi_mode(&mut self) {}
+    fn mark_mode_active(&self) -> bool {
+        false
+    }
+    fn toggle_mark_mode(&mut self) {}
+    fn mark_motion(&mut self, _motion: ViMotion) {}
+    fn mark_select(&mut self, _ty: SelectionType) {}
     fn hint_input(&mut self, _character: char) {}
     fn trigger_hint(&mut self, _hint: &HintMatch) {}
+    fn hint_output_input(&mut self, _key: VirtualKeyCode) {}
+    fn show_clipboard_history(&mut self) {}
+    fn paste_history(&mut self, _index: usize) {}
+    fn cycle_clipboard_history(&mut self) {}
+    fn show_character_picker(&mut self) {}
+    fn picker_input(&mut self, _key: VirtualKeyCode) -> bool {
+        false
+    }
+    fn picker_char(&mut self, _c: char) {}
+    fn record_macro(&mut self, _name: String) {}
+    fn stop_macro_recording(&mut self) {}
+    fn play_macro(&mut self, _name: &str) {}
+    fn record_input(&mut self, _bytes: &[u8]) {}
+    fn foreground_process(&mut self) -> Option<String> {
+        None
+    }
+    fn enter_key_table(&mut self, _table: String, _locked: bool) {}
+    fn leave_key_table(&mut self) {}
+    fn update_key_sequence_timeout(&mut self) {}
     fn paste(&mut self, _text: &str) {}
+    fn confirmation_pending(&self) -> bool {
+        false
+    }
+    fn explicit_confirmation_required(&self) -> bool {
+        false
+    }
+    fn confirm(&mut self, _confirmed: bool) {}
 }
 
 impl Action {
@@ -135,6 +179,7 @@ impl<T: EventListener> Execute<T> for Action {
 
                 ctx.clear_selection();
                 ctx.scroll(Scroll::Bottom);
+                ctx.record_input(s.as_bytes());
                 ctx.write_to_pty(s.clone().into_bytes())
             },
             Action::Command(program) => start_daemon(program.program(), program.args()),
@@ -143,6 +188,21 @@ impl<T: EventListener> Execute<T> for Action {
                 ctx.mark_dirty();
             },
             Action::ToggleViMode => ctx.toggle_vi_mode(),
+            Action::ToggleMarkMode => ctx.toggle_mark_mode(),
+            Action::MarkAction(MarkAction::Left) => ctx.mark_motion(ViMotion::Left),
+            Action::MarkAction(MarkAction::Right) => ctx.mark_motion(ViMotion::Right),
+            Action::MarkAction(MarkAction::Up) => ctx.mark_motion(ViMotion::Up),
+            Action::MarkAction(MarkAction::Down) => ctx.mark_motion(ViMotion::Down),
+            Action::MarkAction(MarkAction::WordLeft) => ctx.mark_motion(ViMotion::SemanticLeft),
+            Action::MarkAction(MarkAction::WordRight) => {
+                ctx.mark_motion(ViMotion::SemanticRightEnd)
+            },
+            Action::MarkAction(MarkAction::SelectWord) => ctx.mark_select(SelectionType::Semantic),
+            Action::MarkAction(MarkAction::SelectLine) => ctx.mark_select(SelectionType::Lines),
+            Action::MarkAction(MarkAction::Copy) => {
+                ctx.copy_selection(ClipboardType::Clipboard);
+                ctx.toggle_mark_mode();
+            },
             Action::ViMotion(motion) => {
                 ctx.on_typing_start();
                 ctx.terminal_mut().vi_motion(*motion);
@@ -233,9 +293,28 @@ impl<T: EventListener> Execute<T> for Action {
                 ctx.search_history_previous()
             },
             Action::SearchAction(SearchAction::SearchHistoryNext) => ctx.search_history_next(),
+            Action::SearchAction(SearchAction::SearchToggleCaseInsensitive) => {
+                ctx.toggle_search_flags(SearchFlags::CASE_INSENSITIVE)
+            },
+            Action::SearchAction(SearchAction::SearchToggleSmartCase) => {
+                ctx.toggle_search_flags(SearchFlags::SMART_CASE)
+            },
+            Action::SearchAction(SearchAction::SearchToggleLiteral) => {
+                ctx.toggle_search_flags(SearchFlags::LITERAL)
+            },
+            Action::SearchAction(SearchAction::SearchToggleWholeWord) => {
+                ctx.toggle_search_flags(SearchFlags::WHOLE_WORD)
+            },
+            Action::SearchAction(SearchAction::SearchToggleFilter) => ctx.toggle_search_filter(),
             Action::SearchForward => ctx.start_search(Direction::Right),
             Action::SearchBackward => ctx.start_search(Direction::Left),
             Action::Copy => ctx.copy_selection(ClipboardType::Clipboard),
+            Action::CopyAnsi => ctx.copy_selection_formatted(CopyFormat::Ansi),
+            Action::CopyHtml => ctx.copy_selection_formatted(CopyFormat::Html),
+            Action::CopyRtf => ctx.copy_selection_formatted(CopyFormat::Rtf),
+            Action::SaveScrollback => ctx.save_content(ExportRegion::History),
+            Action::SaveScreen => ctx.save_content(ExportRegion::Screen),
+            Action::OpenScrollback => ctx.open_scrollback(),
             #[cfg(not(any(target_os = "macos", windows)))]
             Action::CopySelection => ctx.copy_selection(ClipboardType::Selection),
             Action::ClearSelection => ctx.clear_selection(),
@@ -317,6 +396,27 @@ impl<T: EventListener> Execute<T> for Action {
                 ctx.mark_dirty();
             },
             Action::ClearHistory => ctx.terminal_mut().clear_screen(ClearMode::Saved),
+            Action::PasteHintOutput => {
+                let output = ctx.display().hint_output.as_ref();
+                if let Some(text) = output.map(|output| output.stdout().to_owned()) {
+                    ctx.paste(&text);
+                }
+            },
+            Action::Sequence(actions) => {
+                for action in actions {
+                    action.execute(ctx);
+                }
+            },
+            Action::RecordMacro(name) => ctx.record_macro(name.clone()),
+            Action::StopMacroRecording => ctx.stop_macro_recording(),
+            Action::PlayMacro(name) => ctx.play_macro(name),
+            Action::EnterKeyTable(table) => ctx.enter_key_table(table.clone(), false),
+            Action::LockKeyTable(table) => ctx.enter_key_table(table.clone(), true),
+            Action::LeaveKeyTable => ctx.leave_key_table(),
+            Action::ShowClipboardHistory => ctx.show_clipboard_history(),
+            Action::PasteHistory(index) => ctx.paste_history(*index),
+            Action::CycleClipboardHistory => ctx.cycle_clipboard_history(),
+            Action::ShowCharacterPicker => ctx.show_character_picker(),
             Action::ClearLogNotice => ctx.pop_message(),
             Action::SpawnNewInstance => ctx.spawn_new_instance(),
             Action::ReceiveChar | Action::None => (),
@@ -343,6 +443,7 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
 
         let display_offset = self.ctx.terminal().grid().display_offset();
         let old_point = self.ctx.mouse().point(&size_info, display_offset);
+        let old_position = (self.ctx.mouse().x, self.ctx.mouse().y);
 
         let x = min(max(x, 0), size_info.width() as i32 - 1) as usize;
         let y = min(max(y, 0), size_info.height() as i32 - 1) as usize;
@@ -355,8 +456,13 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
         let point = self.ctx.mouse().point(&size_info, display_offset);
         let cell_changed = old_point != point;
 
+        // Pixel-precise mouse reports are sent for every motion, not just cell changes.
+        let report_changed = cell_changed
+            || (self.ctx.terminal().mode().contains(TermMode::SGR_PIXELS_MOUSE)
+                && old_position != (x, y));
+
         // If the mouse hasn't changed cells, do nothing.
-        if !cell_changed
+        if !report_changed
             && self.ctx.mouse().cell_side == cell_side
             && self.ctx.mouse().inside_text_area == inside_text_area
         {
@@ -379,7 +485,7 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
         if (lmb_pressed || rmb_pressed) && (self.ctx.modifiers().shift() || !self.ctx.mouse_mode())
         {
             self.ctx.update_selection(point, cell_side);
-        } else if cell_changed
+        } else if report_changed
             && self.ctx.terminal().mode().intersects(TermMode::MOUSE_MOTION | TermMode::MOUSE_DRAG)
         {
             if lmb_pressed {
@@ -425,29 +531,60 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             return;
         }
 
+        // X10 compatibility mode only reports button presses, without modifiers.
+        let mode = *self.ctx.terminal().mode();
+        let x10 = mode.contains(TermMode::MOUSE_X10);
+        if x10 && state == ElementState::Released {
+            return;
+        }
+
         // Calculate modifiers value.
         let mut mods = 0;
         let modifiers = self.ctx.modifiers();
-        if modifiers.shift() {
+        if modifiers.shift() && !x10 {
             mods += 4;
         }
-        if modifiers.alt() {
+        if modifiers.alt() && !x10 {
             mods += 8;
         }
-        if modifiers.ctrl() {
+        if modifiers.ctrl() && !x10 {
             mods += 16;
         }
 
         // Report mouse events.
-        if self.ctx.terminal().mode().contains(TermMode::SGR_MOUSE) {
-            self.sgr_mouse_report(point, button + mods, state);
-        } else if let ElementState::Released = state {
-            self.normal_mouse_report(point, 3 + mods);
+        if mode.contains(TermMode::SGR_PIXELS_MOUSE) {
+            let (x, y) = self.mouse_pixel_position();
+            self.sgr_mouse_report(x, y, button + mods, state);
+        } else if mode.contains(TermMode::SGR_MOUSE) {
+            self.sgr_mouse_report(point.column.0, point.line.0 as usize, button + mods, state);
         } else {
-            self.normal_mouse_report(point, button + mods);
+            let button = match state {
+                ElementState::Pressed => button + mods,
+                ElementState::Released => 3 + mods,
+            };
+
+            if mode.contains(TermMode::URXVT_MOUSE) {
+                self.urxvt_mouse_report(point, button);
+            } else {
+                self.normal_mouse_report(point, button);
+            }
         }
     }
 
+    /// Mouse position in pixels, relative to the top left corner of the text area.
+    fn mouse_pixel_position(&self) -> (usize, usize) {
+        let size_info = self.ctx.size_info();
+        let mouse = self.ctx.mouse();
+
+        let max_x = (size_info.columns() as f32 * size_info.cell_width()) as usize;
+        let max_y = (size_info.screen_lines() as f32 * size_info.cell_height()) as usize;
+
+        let x = mouse.x.saturating_sub(size_info.padding_x() as usize);
+        let y = mouse.y.saturating_sub(size_info.padding_y() as usize);
+
+        (min(x, max_x.saturating_sub(1)), min(y, max_y.saturating_sub(1)))
+    }
+
     fn normal_mouse_report(&mut self, point: Point, button: u8) {
         let Point { line, column } = point;
         let utf8 = self.ctx.terminal().mode().contains(TermMode::UTF8_MOUSE);
@@ -482,13 +619,18 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
         self.ctx.write_to_pty(msg);
     }
 
-    fn sgr_mouse_report(&mut self, point: Point, button: u8, state: ElementState) {
+    fn urxvt_mouse_report(&mut self, point: Point, button: u8) {
+        let msg = format!("\x1b[{};{};{}M", 32 + button, point.column + 1, point.line + 1);
+        self.ctx.write_to_pty(msg.into_bytes());
+    }
+
+    fn sgr_mouse_report(&mut self, x: usize, y: usize, button: u8, state: ElementState) {
         let c = match state {
             ElementState::Pressed => 'M',
             ElementState::Released => 'm',
         };
 
-        let msg = format!("\x1b[<{};{};{}{}", button, point.column + 1, point.line + 1, c);
+        let msg = format!("\x1b[<{};{};{}{}", button, x + 1, y + 1, c);
         self.ctx.write_to_pty(msg.into_bytes());
     }
 
@@ -529,6 +671,13 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
                 _ => ClickState::Click,
             };
 
+            // Multi-clicks keep changing the selection of the initial click.
+            let mouse = self.ctx.mouse_mut();
+            if mouse.click_state == ClickState::Click {
+                mouse.press_count = mouse.press_count.wrapping_add(1);
+            }
+            mouse.selection_press = Some(mouse.press_count);
+
             // Load mouse point, treating message bar and padding as the closest cell.
             let display_offset = self.ctx.terminal().grid().display_offset();
             let point = self.ctx.mouse().point(&self.ctx.size_info(), display_offset);
@@ -623,6 +772,7 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
                 MouseButton::Other(_) => return,
             };
             self.mouse_report(code, ElementState::Released);
+            self.ctx.mouse_mut().selection_press = None;
             return;
         }
 
@@ -637,6 +787,7 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
 
         // Copy selection on release, to prevent flooding the display server.
         self.ctx.copy_selection(ClipboardType::Selection);
+        self.ctx.mouse_mut().selection_press = None;
     }
 
     pub fn mouse_wheel_input(&mut self, delta: MouseScrollDelta, phase: TouchPhase) {
@@ -761,12 +912,44 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
 
     /// Process key input.
     pub fn key_input(&mut self, input: KeyboardInput) {
+        // Confirm pending requests with a shortcut that ordinary typing can't produce.
+        if self.ctx.confirmation_pending()
+            && input.state == ElementState::Pressed
+            && input.virtual_keycode == Some(VirtualKeyCode::Y)
+            && *self.ctx.modifiers() == ModifiersState::CTRL | ModifiersState::SHIFT
+        {
+            self.ctx.confirm(true);
+            *self.ctx.suppress_chars() = true;
+            return;
+        }
+
         // All key bindings are disabled while a hint is being selected.
         if self.ctx.display().hint_state.active() {
             *self.ctx.suppress_chars() = false;
             return;
         }
 
+        // Keys are used to navigate the hint command output overlay while it's visible.
+        if matches!(&self.ctx.display().hint_output, Some(output) if output.visible()) {
+            if let (ElementState::Pressed, Some(key)) = (input.state, input.virtual_keycode) {
+                self.ctx.hint_output_input(key);
+            }
+            *self.ctx.suppress_chars() = true;
+            return;
+        }
+
+        // Keys are used to navigate the picker overlay, characters filter it.
+        if self.ctx.display().picker.is_some() {
+            if input.state == ElementState::Pressed {
+                let handled = match input.virtual_keycode {
+                    Some(key) => self.ctx.picker_input(key),
+                    None => false,
+                };
+                *self.ctx.suppress_chars() = handled;
+            }
+            return;
+        }
+
         // Reset search delay when the user is still typing.
         if self.ctx.search_active() {
             if let Some(timer) = self.ctx.scheduler_mut().get_mut(TimerId::DelayedSearch) {
@@ -806,15 +989,37 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
     pub fn received_char(&mut self, c: char) {
         let suppress_chars = *self.ctx.suppress_chars();
 
+        // Resolve pending confirmations before anything else.
+        //
+        // Requests made by applications are denied by every character, since the application
+        // could ask the user to type the same character at the same time.
+        if self.ctx.confirmation_pending() && !suppress_chars {
+            let confirmed = !self.ctx.explicit_confirmation_required() && (c == 'y' || c == 'Y');
+            self.ctx.confirm(confirmed);
+            return;
+        }
+
         // Handle hint selection over anything else.
         if self.ctx.display().hint_state.active() && !suppress_chars {
             self.ctx.hint_input(c);
             return;
         }
 
+        // Filter the picker overlay.
+        if self.ctx.display().picker.is_some() {
+            if !suppress_chars {
+                self.ctx.picker_char(c);
+            }
+            return;
+        }
+
         // Pass keys to search and ignore them during `suppress_chars`.
         let search_active = self.ctx.search_active();
-        if suppress_chars || search_active || self.ctx.terminal().mode().contains(TermMode::VI) {
+        if suppress_chars
+            || search_active
+            || self.ctx.terminal().mode().contains(TermMode::VI)
+            || self.ctx.mark_mode_active()
+        {
             if search_active && !suppress_chars {
                 self.ctx.search_input(c);
             }
@@ -842,6 +1047,7 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             bytes.insert(0, b'\x1b');
         }
 
+        self.ctx.record_input(&bytes);
         self.ctx.write_to_pty(bytes);
 
         *self.ctx.received_count() += 1;
@@ -852,13 +1058,54 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
     /// The provided mode, mods, and key must match what is allowed by a binding
     /// for its action to be executed.
     fn process_key_bindings(&mut self, input: KeyboardInput) {
-        let mode = BindingMode::new(self.ctx.terminal().mode(), self.ctx.search_active());
+        let mode = BindingMode::new(
+            self.ctx.terminal().mode(),
+            self.ctx.search_active(),
+            self.ctx.mark_mode_active(),
+        );
         let mods = *self.ctx.modifiers();
         let mut suppress_chars = None;
 
+        // Modifiers are part of the following key press and cannot abort a key sequence.
+        let sequence_active = self.ctx.key_sequence().active();
+        if sequence_active && is_modifier_key(input.virtual_keycode) {
+            return;
+        }
+
+        let keys = self.ctx.key_sequence().keys().to_vec();
+        let table = self.ctx.key_sequence().table().map(String::from);
+
+        // Only look up the foreground process when a binding depends on it.
+        let process = if requires_process(self.ctx.config().ui_config.key_bindings()) {
+            self.ctx.foreground_process()
+        } else {
+            None
+        };
+        let mut next_key = None;
+        let mut triggered = Vec::new();
+
         for i in 0..self.ctx.config().ui_config.key_bindings().len() {
             let binding = &self.ctx.config().ui_config.key_bindings()[i];
 
+            if binding.table != table
+                || !binding.prefix.starts_with(&keys)
+                || !binding.process_matches(process.as_deref())
+            {
+                continue;
+            }
+
+            // Advance the key sequence if it can still be completed by this binding.
+            if let Some(&prefix_key) = binding.prefix.get(keys.len()) {
+                if prefix_key.mods.into_inner() == mods
+                    && key_matches(prefix_key.key, &input)
+                    && mode.contains(binding.mode)
+                    && !mode.intersects(binding.notmode)
+                {
+                    next_key = Some(prefix_key);
+                }
+                continue;
+            }
+
             let key = match (binding.trigger, input.virtual_keycode) {
                 (Key::Scancode(_), _) => Key::Scancode(input.scancode),
                 (_, Some(key)) => Key::Keycode(key),
@@ -866,11 +1113,46 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             };
 
             if binding.is_triggered_by(mode, mods, &key) {
-                // Pass through the key if any of the bindings has the `ReceiveChar` action.
-                *suppress_chars.get_or_insert(true) &= binding.action != Action::ReceiveChar;
+                triggered.push((binding.process.is_some(), binding.action.clone()));
+            }
+        }
 
-                // Binding was triggered; run the action.
-                binding.action.clone().execute(&mut self.ctx);
+        for action in shadow_unconditional(triggered) {
+            // Pass through the key if any of the bindings has the `ReceiveChar` action.
+            *suppress_chars.get_or_insert(true) &= action != Action::ReceiveChar;
+
+            // Binding was triggered; complete the key sequence and run the action.
+            self.ctx.key_sequence().finish();
+            action.execute(&mut self.ctx);
+        }
+
+        if suppress_chars.is_none() {
+            if let Some(next_key) = next_key {
+                self.ctx.key_sequence().push(next_key);
+                suppress_chars = Some(true);
+            } else if sequence_active {
+                // Swallow keys which do not complete the pending key sequence.
+                self.ctx.key_sequence().finish();
+                suppress_chars = Some(true);
+            }
+        }
+
+        if sequence_active || self.ctx.key_sequence().active() {
+            self.ctx.update_key_sequence_timeout();
+            self.ctx.mark_dirty();
+        }
+
+        // Report modified keys without binding using xterm's modifyOtherKeys encoding.
+        if suppress_chars.is_none()
+            && !mode.intersects(BindingMode::VI | BindingMode::SEARCH | BindingMode::MARK)
+            && !self.ctx.confirmation_pending()
+        {
+            let term_mode = *self.ctx.terminal().mode();
+            let sequence =
+                input.virtual_keycode.and_then(|key| modify_other_keys(term_mode, key, mods));
+            if let Some(sequence) = sequence {
+                Action::Esc(sequence).execute(&mut self.ctx);
+                suppress_chars = Some(true);
             }
         }
 
@@ -883,10 +1165,22 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
     /// The provided mode, mods, and key must match what is allowed by a binding
     /// for its action to be executed.
     fn process_mouse_bindings(&mut self, button: MouseButton) {
-        let mode = BindingMode::new(self.ctx.terminal().mode(), self.ctx.search_active());
+        let mode = BindingMode::new(
+            self.ctx.terminal().mode(),
+            self.ctx.search_active(),
+            self.ctx.mark_mode_active(),
+        );
         let mouse_mode = self.ctx.mouse_mode();
         let mods = *self.ctx.modifiers();
 
+        // Only look up the foreground process when a binding depends on it.
+        let process = if requires_process(self.ctx.config().ui_config.mouse_bindings()) {
+            self.ctx.foreground_process()
+        } else {
+            None
+        };
+
+        let mut triggered = Vec::new();
         for i in 0..self.ctx.config().ui_config.mouse_bindings().len() {
             let mut binding = self.ctx.config().ui_config.mouse_bindings()[i].clone();
 
@@ -895,10 +1189,16 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
                 binding.mods |= ModifiersState::SHIFT;
             }
 
-            if binding.is_triggered_by(mode, mods, &button) {
-                binding.action.execute(&mut self.ctx);
+            if binding.is_triggered_by(mode, mods, &button)
+                && binding.process_matches(process.as_deref())
+            {
+                triggered.push((binding.process.is_some(), binding.action));
             }
         }
+
+        for action in shadow_unconditional(triggered) {
+            action.execute(&mut self.ctx);
+        }
     }
 
     /// Check mouse icon state in relation to the message bar.
@@ -989,14 +1289,130 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
     }
 }
 
+/// Check if a pressed key is one of the modifiers.
+fn is_modifier_key(key: Option<VirtualKeyCode>) -> bool {
+    matches!(
+        key,
+        Some(VirtualKeyCode::LShift)
+            | Some(VirtualKeyCode::RShift)
+            | Some(VirtualKeyCode::LControl)
+            | Some(VirtualKeyCode::RControl)
+            | Some(VirtualKeyCode::LAlt)
+            | Some(VirtualKeyCode::RAlt)
+            | Some(VirtualKeyCode::LWin)
+            | Some(VirtualKeyCode::RWin)
+    )
+}
+
+/// Check if a key of a key sequence matches the keyboard input.
+fn key_matches(key: Key, input: &KeyboardInput) -> bool {
+    match key {
+        Key::Scancode(scancode) => scancode == input.scancode,
+        Key::Keycode(key) => Some(key) == input.virtual_keycode,
+    }
+}
+
+/// Encode a modified key as `CSI 27 ; modifiers ; code ~` for xterm's modifyOtherKeys.
+///
+/// With `MODIFY_OTHER_KEYS`, only keys which cannot be distinguished otherwise are encoded, like
+/// Ctrl+Enter, Shift+Space or Ctrl+Period. With `MODIFY_ALL_KEYS`, all keys with Ctrl or Alt are
+/// encoded too.
+fn modify_other_keys(mode: TermMode, key: VirtualKeyCode, mods: ModifiersState) -> Option<String> {
+    let all = mode.contains(TermMode::MODIFY_ALL_KEYS);
+    if !all && !mode.contains(TermMode::MODIFY_OTHER_KEYS) {
+        return None;
+    }
+
+    let (code, special) = match key {
+        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => ('\r', true),
+        VirtualKeyCode::Tab => ('\t', true),
+        VirtualKeyCode::Space => (' ', true),
+        VirtualKeyCode::Back => ('\x7f', true),
+        VirtualKeyCode::Escape => ('\x1b', true),
+        VirtualKeyCode::Key0 => ('0', false),
+        VirtualKeyCode::Key1 => ('1', false),
+        VirtualKeyCode::Key2 => ('2', false),
+        VirtualKeyCode::Key3 => ('3', false),
+        VirtualKeyCode::Key4 => ('4', false),
+        VirtualKeyCode::Key5 => ('5', false),
+        VirtualKeyCode::Key6 => ('6', false),
+        VirtualKeyCode::Key7 => ('7', false),
+        VirtualKeyCode::Key8 => ('8', false),
+        VirtualKeyCode::Key9 => ('9', false),
+        VirtualKeyCode::Apostrophe => ('\'', false),
+        VirtualKeyCode::Backslash => ('\\', false),
+        VirtualKeyCode::Comma => (',', false),
+        VirtualKeyCode::Equals => ('=', false),
+        VirtualKeyCode::Grave => ('`', false),
+        VirtualKeyCode::LBracket => ('[', false),
+        VirtualKeyCode::Minus => ('-', false),
+        VirtualKeyCode::Period => ('.', false),
+        VirtualKeyCode::RBracket => (']', false),
+        VirtualKeyCode::Semicolon => (';', false),
+        VirtualKeyCode::Slash => ('/', false),
+        // Letters are laid out contiguously from `A` to `Z`.
+        key if (VirtualKeyCode::A as u32..=VirtualKeyCode::Z as u32).contains(&(key as u32)) => {
+            let base = if mods.shift() { b'A' } else { b'a' };
+            ((base + (key as u32 - VirtualKeyCode::A as u32) as u8) as char, false)
+        },
+        _ => return None,
+    };
+
+    let encode = if mods.logo() || mods.is_empty() {
+        false
+    } else if special {
+        // Alt prefixes keys with an escape and Ctrl+Space sends a null byte.
+        let well_defined = !(mods.shift() || mods.ctrl())
+            || (key == VirtualKeyCode::Space && mods == ModifiersState::CTRL);
+        all || !well_defined
+    } else if all {
+        // Printable keys are only encoded when the character is modified by Ctrl or Alt.
+        mods.ctrl() || mods.alt()
+    } else {
+        // Ctrl only maps letters and some brackets to control characters.
+        let control_char = match code {
+            'a'..='z' | 'A'..='Z' => true,
+            '[' | '\\' | ']' => !mods.shift(),
+            _ => false,
+        };
+        mods.ctrl() && !control_char
+    };
+
+    if !encode {
+        return None;
+    }
+
+    let modifiers = 1 + mods.shift() as u8 + 2 * mods.alt() as u8 + 4 * mods.ctrl() as u8;
+    Some(format!("\x1b[27;{};{}~", modifiers, code as u32))
+}
+
+/// Check if any of the bindings depends on the foreground process.
+fn requires_process<T>(bindings: &[Binding<T>]) -> bool {
+    bindings.iter().any(|binding| binding.process.is_some())
+}
+
+/// Drop unconditional actions if a binding for the foreground process was triggered.
+///
+/// The actions are expected to be tagged with whether their binding has a process condition.
+fn shadow_unconditional(triggered: Vec<(bool, Action)>) -> impl Iterator<Item = Action> {
+    let process_specific = triggered.iter().any(|(process, _)| *process);
+    triggered
+        .into_iter()
+        .filter(move |(process, _)| *process || !process_specific)
+        .map(|(_, action)| action)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::cell::RefCell;
+
     use glutin::event::{Event as GlutinEvent, VirtualKeyCode, WindowEvent};
 
     use alacritty_terminal::event::Event as TerminalEvent;
 
+    use crate::config::ui_config::UiConfig;
     use crate::config::Binding;
     use crate::message_bar::MessageBuffer;
 
@@ -1014,6 +1430,10 @@ mod tests {
         pub received_count: usize,
         pub suppress_chars: bool,
         pub modifiers: ModifiersState,
+        pub pending_confirmation: Option<bool>,
+        pub confirmed: Option<bool>,
+        pub key_sequence: KeySequence,
+        pub written: RefCell<Vec<u8>>,
         config: &'a Config,
     }
 
@@ -1089,6 +1509,14 @@ mod tests {
             unimplemented!();
         }
 
+        fn key_sequence(&mut self) -> &mut KeySequence {
+            &mut self.key_sequence
+        }
+
+        fn write_to_pty<B: Into<Cow<'static, [u8]>>>(&self, data: B) {
+            self.written.borrow_mut().extend_from_slice(&data.into());
+        }
+
         fn pop_message(&mut self) {
             self.message_buffer.pop();
         }
@@ -1112,6 +1540,19 @@ mod tests {
         fn scheduler_mut(&mut self) -> &mut Scheduler {
             unimplemented!();
         }
+
+        fn confirmation_pending(&self) -> bool {
+            self.pending_confirmation.is_some()
+        }
+
+        fn explicit_confirmation_required(&self) -> bool {
+            self.pending_confirmation == Some(true)
+        }
+
+        fn confirm(&mut self, confirmed: bool) {
+            self.pending_confirmation = None;
+            self.confirmed = Some(confirmed);
+        }
     }
 
     macro_rules! test_clickstate {
@@ -1154,6 +1595,10 @@ mod tests {
                     suppress_chars: false,
                     modifiers: Default::default(),
                     message_buffer: &mut message_buffer,
+                    pending_confirmation: None,
+                    confirmed: None,
+                    key_sequence: KeySequence::default(),
+                    written: RefCell::new(Vec::new()),
                     config: &cfg,
                 };
 
@@ -1177,6 +1622,184 @@ mod tests {
         }
     }
 
+    /// Answer a pending confirmation by pressing the confirmation shortcut or typing a character.
+    fn answer_confirmation(explicit: bool, input: Result<ModifiersState, char>) -> Option<bool> {
+        let mut clipboard = Clipboard::new_nop();
+        let cfg = Config::default();
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0., 0., false);
+        let mut terminal = Term::new(&cfg, size, MockEventProxy);
+        let mut mouse = Mouse::default();
+        let mut message_buffer = MessageBuffer::new();
+
+        let context = ActionContext {
+            terminal: &mut terminal,
+            mouse: &mut mouse,
+            size_info: &size,
+            clipboard: &mut clipboard,
+            received_count: 0,
+            suppress_chars: false,
+            modifiers: Default::default(),
+            message_buffer: &mut message_buffer,
+            pending_confirmation: Some(explicit),
+            confirmed: None,
+            key_sequence: KeySequence::default(),
+            written: RefCell::new(Vec::new()),
+            config: &cfg,
+        };
+        let mut processor = Processor::new(context);
+
+        match input {
+            Ok(mods) => {
+                processor.ctx.modifiers = mods;
+
+                #[allow(deprecated)]
+                processor.key_input(KeyboardInput {
+                    scancode: 0,
+                    state: ElementState::Pressed,
+                    virtual_keycode: Some(VirtualKeyCode::Y),
+                    modifiers: mods,
+                });
+
+                // The control character produced by the shortcut must not reach the PTY.
+                assert!(processor.ctx.suppress_chars);
+            },
+            Err(c) => processor.received_char(c),
+        }
+
+        processor.ctx.confirmed
+    }
+
+    #[test]
+    fn confirm_user_request() {
+        assert_eq!(answer_confirmation(false, Err('y')), Some(true));
+        assert_eq!(answer_confirmation(false, Err('Y')), Some(true));
+        assert_eq!(answer_confirmation(false, Err('n')), Some(false));
+    }
+
+    #[test]
+    fn confirm_application_request() {
+        let mods = ModifiersState::CTRL | ModifiersState::SHIFT;
+        assert_eq!(answer_confirmation(true, Ok(mods)), Some(true));
+        assert_eq!(answer_confirmation(true, Err('y')), Some(false));
+        assert_eq!(answer_confirmation(true, Err('Y')), Some(false));
+    }
+
+    /// Press keys with the bindings of a config, returning the keys' `suppress_chars` states.
+    ///
+    /// The key sequence is started in the `table` key table, if any.
+    fn press_keys(
+        bindings: &str,
+        table: Option<(&str, bool)>,
+        keys: &[(VirtualKeyCode, ModifiersState)],
+    ) -> (Vec<bool>, KeySequence, String) {
+        let mut clipboard = Clipboard::new_nop();
+        let ui_config = serde_yaml::from_str::<UiConfig>(bindings).unwrap();
+        let cfg = Config { ui_config, ..Config::default() };
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0., 0., false);
+        let mut terminal = Term::new(&cfg, size, MockEventProxy);
+        let mut mouse = Mouse::default();
+        let mut message_buffer = MessageBuffer::new();
+
+        let mut key_sequence = KeySequence::default();
+        if let Some((table, locked)) = table {
+            key_sequence.enter_table(table.into(), locked);
+        }
+
+        let context = ActionContext {
+            terminal: &mut terminal,
+            mouse: &mut mouse,
+            size_info: &size,
+            clipboard: &mut clipboard,
+            received_count: 0,
+            suppress_chars: false,
+            modifiers: Default::default(),
+            message_buffer: &mut message_buffer,
+            pending_confirmation: None,
+            confirmed: None,
+            key_sequence,
+            written: RefCell::new(Vec::new()),
+            config: &cfg,
+        };
+        let mut processor = Processor::new(context);
+
+        let mut suppressed = Vec::new();
+        for &(key, mods) in keys {
+            processor.ctx.modifiers = mods;
+
+            #[allow(deprecated)]
+            processor.process_key_bindings(KeyboardInput {
+                scancode: 0,
+                state: ElementState::Pressed,
+                virtual_keycode: Some(key),
+                modifiers: mods,
+            });
+
+            suppressed.push(processor.ctx.suppress_chars);
+        }
+
+        let written = String::from_utf8(processor.ctx.written.into_inner()).unwrap();
+        (suppressed, processor.ctx.key_sequence, written)
+    }
+
+    #[test]
+    fn process_key_sequence() {
+        let bindings =
+            "key_bindings: [{ key: B, prefix: [{ key: A, mods: Control }], chars: 'x' }]";
+        let keys = [
+            (VirtualKeyCode::A, ModifiersState::CTRL),
+            (VirtualKeyCode::B, ModifiersState::empty()),
+        ];
+
+        let (suppressed, sequence, written) = press_keys(bindings, None, &keys);
+        assert_eq!(suppressed, vec![true, true]);
+        assert!(!sequence.active());
+        assert_eq!(written, "x");
+
+        // The trigger alone does not complete the sequence.
+        let (suppressed, _, written) = press_keys(bindings, None, &keys[1..]);
+        assert_eq!(suppressed, vec![false]);
+        assert_eq!(written, "");
+    }
+
+    #[test]
+    fn process_aborted_key_sequence() {
+        let bindings =
+            "key_bindings: [{ key: B, prefix: [{ key: A, mods: Control }], chars: 'x' }]";
+        let keys = [
+            (VirtualKeyCode::A, ModifiersState::CTRL),
+            (VirtualKeyCode::C, ModifiersState::empty()),
+            (VirtualKeyCode::B, ModifiersState::empty()),
+        ];
+
+        // The key aborting the sequence is swallowed and the sequence must be started again.
+        let (suppressed, sequence, written) = press_keys(bindings, None, &keys);
+        assert_eq!(suppressed, vec![true, true, false]);
+        assert!(!sequence.active());
+        assert_eq!(written, "");
+    }
+
+    #[test]
+    fn process_locked_key_table() {
+        let bindings =
+            "key_bindings: [{ key: B, table: resize, chars: 'x' }, { key: B, chars: 'y' }]";
+        let keys = [
+            (VirtualKeyCode::B, ModifiersState::empty()),
+            (VirtualKeyCode::C, ModifiersState::empty()),
+            (VirtualKeyCode::B, ModifiersState::empty()),
+        ];
+
+        // Bindings of the table stay active, while bindings outside of it are disabled.
+        let (suppressed, sequence, written) = press_keys(bindings, Some(("resize", true)), &keys);
+        assert_eq!(suppressed, vec![true, true, true]);
+        assert_eq!(sequence.table(), Some("resize"));
+        assert_eq!(written, "xx");
+
+        // Unlocked tables are left after the first key.
+        let (_, sequence, written) = press_keys(bindings, Some(("resize", false)), &keys);
+        assert_eq!(sequence.table(), None);
+        assert_eq!(written, "xy");
+    }
+
     macro_rules! test_process_binding {
         {
             name: $name:ident,
@@ -1294,7 +1917,7 @@ mod tests {
 
     test_process_binding! {
         name: process_binding_nomode_shiftmod_require_shift,
-        binding: Binding { trigger: KEY, mods: ModifiersState::SHIFT, action: Action::from("\x1b[1;2D"), mode: BindingMode::empty(), notmode: BindingMode::empty() },
+        binding: Binding { trigger: KEY, mods: ModifiersState::SHIFT, action: Action::from("\x1b[1;2D"), mode: BindingMode::empty(), notmode: BindingMode::empty(), prefix: Vec::new(), table: None, process: None },
         triggers: true,
         mode: BindingMode::empty(),
         mods: ModifiersState::SHIFT,
@@ -1302,7 +1925,7 @@ mod tests {
 
     test_process_binding! {
         name: process_binding_nomode_nomod_require_shift,
-        binding: Binding { trigger: KEY, mods: ModifiersState::SHIFT, action: Action::from("\x1b[1;2D"), mode: BindingMode::empty(), notmode: BindingMode::empty() },
+        binding: Binding { trigger: KEY, mods: ModifiersState::SHIFT, action: Action::from("\x1b[1;2D"), mode: BindingMode::empty(), notmode: BindingMode::empty(), prefix: Vec::new(), table: None, process: None },
         triggers: false,
         mode: BindingMode::empty(),
         mods: ModifiersState::empty(),
@@ -1310,7 +1933,7 @@ mod tests {
 
     test_process_binding! {
         name: process_binding_nomode_controlmod,
-        binding: Binding { trigger: KEY, mods: ModifiersState::CTRL, action: Action::from("\x1b[1;5D"), mode: BindingMode::empty(), notmode: BindingMode::empty() },
+        binding: Binding { trigger: KEY, mods: ModifiersState::CTRL, action: Action::from("\x1b[1;5D"), mode: BindingMode::empty(), notmode: BindingMode::empty(), prefix: Vec::new(), table: None, process: None },
         triggers: true,
         mode: BindingMode::empty(),
         mods: ModifiersState::CTRL,
@@ -1318,7 +1941,7 @@ mod tests {
 
     test_process_binding! {
         name: process_binding_nomode_nomod_require_not_appcursor,
-        binding: Binding { trigger: KEY, mods: ModifiersState::empty(), action: Action::from("\x1b[D"), mode: BindingMode::empty(), notmode: BindingMode::APP_CURSOR },
+        binding: Binding { trigger: KEY, mods: ModifiersState::empty(), action: Action::from("\x1b[D"), mode: BindingMode::empty(), notmode: BindingMode::APP_CURSOR, prefix: Vec::new(), table: None, process: None },
         triggers: true,
         mode: BindingMode::empty(),
         mods: ModifiersState::empty(),
@@ -1326,7 +1949,7 @@ mod tests {
 
     test_process_binding! {
         name: process_binding_appcursormode_nomod_require_appcursor,
-        binding: Binding { trigger: KEY, mods: ModifiersState::empty(), action: Action::from("\x1bOD"), mode: BindingMode::APP_CURSOR, notmode: BindingMode::empty() },
+        binding: Binding { trigger: KEY, mods: ModifiersState::empty(), action: Action::from("\x1bOD"), mode: BindingMode::APP_CURSOR, notmode: BindingMode::empty(), prefix: Vec::new(), table: None, process: None },
         triggers: true,
         mode: BindingMode::APP_CURSOR,
         mods: ModifiersState::empty(),
@@ -1334,7 +1957,7 @@ mod tests {
 
     test_process_binding! {
         name: process_binding_nomode_nomod_require_appcursor,
-        binding: Binding { trigger: KEY, mods: ModifiersState::empty(), action: Action::from("\x1bOD"), mode: BindingMode::APP_CURSOR, notmode: BindingMode::empty() },
+        binding: Binding { trigger: KEY, mods: ModifiersState::empty(), action: Action::from("\x1bOD"), mode: BindingMode::APP_CURSOR, notmode: BindingMode::empty(), prefix: Vec::new(), table: None, process: None },
         triggers: false,
         mode: BindingMode::empty(),
         mods: ModifiersState::empty(),
@@ -1342,7 +1965,7 @@ mod tests {
 
     test_process_binding! {
         name: process_binding_appcursormode_appkeypadmode_nomod_require_appcursor,
-        binding: Binding { trigger: KEY, mods: ModifiersState::empty(), action: Action::from("\x1bOD"), mode: BindingMode::APP_CURSOR, notmode: BindingMode::empty() },
+        binding: Binding { trigger: KEY, mods: ModifiersState::empty(), action: Action::from("\x1bOD"), mode: BindingMode::APP_CURSOR, notmode: BindingMode::empty(), prefix: Vec::new(), table: None, process: None },
         triggers: true,
         mode: BindingMode::APP_CURSOR | BindingMode::APP_KEYPAD,
         mods: ModifiersState::empty(),
@@ -1350,9 +1973,56 @@ mod tests {
 
     test_process_binding! {
         name: process_binding_fail_with_extra_mods,
-        binding: Binding { trigger: KEY, mods: ModifiersState::LOGO, action: Action::from("arst"), mode: BindingMode::empty(), notmode: BindingMode::empty() },
+        binding: Binding { trigger: KEY, mods: ModifiersState::LOGO, action: Action::from("arst"), mode: BindingMode::empty(), notmode: BindingMode::empty(), prefix: Vec::new(), table: None, process: None },
         triggers: false,
         mode: BindingMode::empty(),
         mods: ModifiersState::ALT | ModifiersState::LOGO,
     }
+
+    #[test]
+    fn process_bindings_shadow_unconditional() {
+        let shell = vec![(false, Action::from("a")), (false, Action::ReceiveChar)];
+        let actions: Vec<_> = shadow_unconditional(shell.clone()).collect();
+        assert_eq!(actions, vec![Action::from("a"), Action::ReceiveChar]);
+
+        let mut vim = shell;
+        vim.push((true, Action::from("b")));
+        let actions: Vec<_> = shadow_unconditional(vim).collect();
+        assert_eq!(actions, vec![Action::from("b")]);
+    }
+
+    #[test]
+    fn modify_other_keys_encoding() {
+        let level_one = TermMode::MODIFY_OTHER_KEYS;
+        let level_two = TermMode::MODIFY_ALL_KEYS;
+        let ctrl = ModifiersState::CTRL;
+        let shift = ModifiersState::SHIFT;
+
+        let encode = |mode, key, mods| modify_other_keys(mode, key, mods);
+
+        assert_eq!(encode(TermMode::NONE, VirtualKeyCode::Return, ctrl), None);
+        assert_eq!(encode(level_one, VirtualKeyCode::Return, ctrl).unwrap(), "\x1b[27;5;13~");
+        assert_eq!(encode(level_one, VirtualKeyCode::Space, shift).unwrap(), "\x1b[27;2;32~");
+        assert_eq!(encode(level_one, VirtualKeyCode::Space, ctrl), None);
+        assert_eq!(encode(level_one, VirtualKeyCode::Return, ModifiersState::ALT), None);
+        assert_eq!(encode(level_one, VirtualKeyCode::A, ctrl), None);
+        assert_eq!(encode(level_one, VirtualKeyCode::A, ctrl | shift), None);
+        assert_eq!(encode(level_one, VirtualKeyCode::Period, ctrl).unwrap(), "\x1b[27;5;46~");
+        assert_eq!(encode(level_one, VirtualKeyCode::Semicolon, ctrl).unwrap(), "\x1b[27;5;59~");
+        assert_eq!(encode(level_one, VirtualKeyCode::Key1, ctrl).unwrap(), "\x1b[27;5;49~");
+        assert_eq!(encode(level_one, VirtualKeyCode::Key1, ctrl | shift).unwrap(), "\x1b[27;6;49~");
+        assert_eq!(encode(level_one, VirtualKeyCode::LBracket, ctrl), None);
+        assert_eq!(
+            encode(level_one, VirtualKeyCode::LBracket, ctrl | shift).unwrap(),
+            "\x1b[27;6;91~"
+        );
+        assert_eq!(encode(level_one, VirtualKeyCode::Key1, ModifiersState::ALT), None);
+        assert_eq!(encode(level_one, VirtualKeyCode::Key1, shift), None);
+
+        assert_eq!(encode(level_two, VirtualKeyCode::A, ctrl).unwrap(), "\x1b[27;5;97~");
+        assert_eq!(encode(level_two, VirtualKeyCode::A, ctrl | shift).unwrap(), "\x1b[27;6;65~");
+        assert_eq!(encode(level_two, VirtualKeyCode::Space, ctrl).unwrap(), "\x1b[27;5;32~");
+        assert_eq!(encode(level_two, VirtualKeyCode::A, shift), None);
+        assert_eq!(encode(level_two, VirtualKeyCode::Left, ctrl), None);
+    }
 }
@@ -0,0 +1,205 @@
+//! Recording and replaying typed input.
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use log::{debug, warn};
+
+/// Name of the file storing the macros, next to the configuration file.
+const MACROS_FILE_NAME: &str = "alacritty-macros.yml";
+
+/// Named macros containing typed input.
+#[derive(Default, Debug)]
+pub struct Macros {
+    /// Recorded input by macro name.
+    macros: BTreeMap<String, String>,
+
+    /// Name and input of the macro which is currently being recorded.
+    recording: Option<(String, String)>,
+
+    /// File the macros are persisted in.
+    ///
+    /// This is removed after the first failure to write it, keeping macros only in memory.
+    path: Option<PathBuf>,
+}
+
+impl Macros {
+    /// Load the macros stored next to the configuration file.
+    ///
+    /// Without a configuration file, macros are only kept in memory. The same applies if the
+    /// existing macro file cannot be read, to never overwrite a file which isn't ours.
+    pub fn load(config_path: Option<&Path>) -> Self {
+        let mut path = config_path.and_then(Path::parent).map(|dir| dir.join(MACROS_FILE_NAME));
+
+        let macros = match path.as_ref().map(fs::read_to_string) {
+            Some(Ok(macros)) => match serde_yaml::from_str(&macros) {
+                Ok(macros) => macros,
+                Err(err) => {
+                    warn!("Unable to parse macros, keeping them in memory only: {}", err);
+                    path = None;
+                    BTreeMap::new()
+                },
+            },
+            Some(Err(err)) if err.kind() != io::ErrorKind::NotFound => {
+                warn!("Unable to read macros, keeping them in memory only: {}", err);
+                path = None;
+                BTreeMap::new()
+            },
+            _ => BTreeMap::new(),
+        };
+
+        Self { macros, recording: None, path }
+    }
+
+    /// Name of the macro which is currently being recorded.
+    pub fn recording(&self) -> Option<&str> {
+        self.recording.as_ref().map(|(name, _)| name.as_str())
+    }
+
+    /// Start recording a macro, replacing any macro with the same name.
+    pub fn start_recording(&mut self, name: String) {
+        self.recording = Some((name, String::new()));
+    }
+
+    /// Stop recording and persist the recorded macro.
+    ///
+    /// Once saving failed, macros are only stored in memory without reporting further errors.
+    pub fn stop_recording(&mut self) -> io::Result<()> {
+        if let Some((name, input)) = self.recording.take() {
+            self.macros.insert(name, input);
+            self.save()?;
+        }
+
+        Ok(())
+    }
+
+    /// Add input to the macro which is currently being recorded.
+    pub fn record(&mut self, input: &str) {
+        if let Some((_, recorded)) = &mut self.recording {
+            recorded.push_str(input);
+        }
+    }
+
+    /// Get the input recorded for a macro.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.macros.get(name).map(String::as_str)
+    }
+
+    /// Write all macros to their file.
+    fn save(&mut self) -> io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => {
+                debug!("Not saving macros without writable macro file");
+                return Ok(());
+            },
+        };
+
+        let result = write_private(path, &self.macros);
+        if result.is_err() {
+            self.path = None;
+        }
+        result
+    }
+}
+
+/// Write macros to a file which is only accessible by the current user.
+fn write_private(path: &Path, macros: &BTreeMap<String, String>) -> io::Result<()> {
+    let macros = serde_yaml::to_string(macros)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    let mut file = options.open(path)?;
+
+    // Restrict files created before their permissions were set on creation.
+    #[cfg(unix)]
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+
+    file.write_all(macros.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn record_macro() {
+        let mut macros = Macros::default();
+        macros.record("ignored");
+
+        macros.start_recording(String::from("test"));
+        assert_eq!(macros.recording(), Some("test"));
+        macros.record("ls");
+        macros.record("\r");
+
+        macros.stop_recording().unwrap();
+        assert_eq!(macros.recording(), None);
+        assert_eq!(macros.get("test"), Some("ls\r"));
+    }
+
+    #[test]
+    fn persist_macros() {
+        let dir = env::temp_dir().join(format!("alacritty-macros-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("alacritty.yml");
+
+        let mut macros = Macros::load(Some(&config_path));
+        macros.start_recording(String::from("escape"));
+        macros.record("\x1b[A\n");
+        macros.stop_recording().unwrap();
+
+        let macros = Macros::load(Some(&config_path));
+        assert_eq!(macros.get("escape"), Some("\x1b[A\n"));
+
+        #[cfg(unix)]
+        {
+            let metadata = fs::metadata(dir.join(MACROS_FILE_NAME)).unwrap();
+            assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn foreign_file_not_overwritten() {
+        let dir = env::temp_dir().join(format!("alacritty-foreign-macros-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(MACROS_FILE_NAME);
+        fs::write(&path, "- not a macro\n").unwrap();
+
+        let mut macros = Macros::load(Some(&dir.join("alacritty.yml")));
+        macros.start_recording(String::from("test"));
+        macros.stop_recording().unwrap();
+
+        assert_eq!(macros.get("test"), Some(""));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "- not a macro\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn unwritable_macros() {
+        let dir = env::temp_dir().join(format!("alacritty-missing-{}", process::id()));
+        let config_path = dir.join("alacritty.yml");
+        let mut macros = Macros::load(Some(&config_path));
+
+        // Only the first failure is reported, macros are still kept in memory.
+        macros.start_recording(String::from("first"));
+        assert!(macros.stop_recording().is_err());
+        macros.start_recording(String::from("second"));
+        assert!(macros.stop_recording().is_ok());
+        assert_eq!(macros.get("first"), Some(""));
+        assert_eq!(macros.get("second"), Some(""));
+    }
+}
@@ -33,18 +33,26 @@ use alacritty_terminal::tty;
 mod cli;
 mod clipboard;
 mod config;
+mod copy_format;
 mod daemon;
 mod display;
 mod event;
+mod export;
+mod foreground_process;
 mod input;
 mod logging;
 #[cfg(target_os = "macos")]
 mod macos;
+mod macros;
 mod message_bar;
 #[cfg(windows)]
 mod panic;
+mod paste;
 mod renderer;
 mod scheduler;
+mod search_history;
+#[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+mod selection_owner;
 
 mod gl {
     #![allow(clippy::all)]
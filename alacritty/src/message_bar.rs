@@ -26,6 +26,9 @@ pub enum MessageType {
 
     /// A message represents a warning.
     Warning,
+
+    /// A message represents general information.
+    Info,
 }
 
 impl Message {
@@ -0,0 +1,151 @@
+//! Inspection and sanitization of pasted text.
+
+use crate::config::drag_and_drop::PathQuoting;
+
+/// Escape character.
+const ESC: char = '\x1b';
+
+/// Check if pasted text could execute commands without further user input.
+pub fn requires_confirmation(text: &str) -> bool {
+    text.chars().any(|c| c != '\t' && c.is_control())
+}
+
+/// Remove escape sequences and control characters, except for tabs and line breaks.
+///
+/// This also removes nested bracketed paste terminators like `ESC[201~`, preventing the text
+/// from leaving bracketed paste mode early.
+pub fn sanitize(text: &str) -> String {
+    let mut sanitized = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\t' | '\n' | '\r' => sanitized.push(c),
+            // CSI sequences are terminated by a byte in the range `@` to `~`.
+            '\u{9b}' => skip_csi(&mut chars),
+            // Control strings are terminated by BEL or ST.
+            '\u{90}' | '\u{98}' | '\u{9d}' | '\u{9e}' | '\u{9f}' => skip_string(&mut chars),
+            ESC => match chars.next() {
+                Some('[') => skip_csi(&mut chars),
+                Some('P') | Some('X') | Some(']') | Some('^') | Some('_') => {
+                    skip_string(&mut chars)
+                },
+                _ => (),
+            },
+            c if c.is_control() => (),
+            c => sanitized.push(c),
+        }
+    }
+
+    sanitized
+}
+
+/// Escape a path for use as a single shell argument.
+///
+/// Paths which only contain characters without special meaning to the shell are not quoted.
+/// Relative paths starting with `-` are prefixed with `./`, so they are not parsed as options.
+pub fn escape_path(path: &str, quoting: PathQuoting) -> String {
+    let prefixed;
+    let path = if path.starts_with('-') {
+        prefixed = format!("./{}", path);
+        &prefixed
+    } else {
+        path
+    };
+
+    if !path.is_empty() && path.chars().all(is_shell_safe) {
+        return path.to_owned();
+    }
+
+    match quoting {
+        // Backslashes cannot escape line breaks, so those are quoted instead.
+        PathQuoting::Backslash if !path.chars().any(char::is_control) => {
+            let mut escaped = String::with_capacity(path.len() * 2);
+            for c in path.chars() {
+                if !is_shell_safe(c) {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+            escaped
+        },
+        PathQuoting::Double => {
+            let mut escaped = String::with_capacity(path.len() + 2);
+            escaped.push('"');
+            for c in path.chars() {
+                match c {
+                    // Escaping `!` would keep the backslash, so it is single quoted instead.
+                    '!' => escaped.push_str("\"'!'\""),
+                    '"' | '\\' | '$' | '`' => {
+                        escaped.push('\\');
+                        escaped.push(c);
+                    },
+                    _ => escaped.push(c),
+                }
+            }
+            escaped.push('"');
+            escaped
+        },
+        _ => format!("'{}'", path.replace('\'', "'\\''")),
+    }
+}
+
+/// Check if a character can be used in a shell argument without escaping it.
+fn is_shell_safe(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | '+' | ',' | ':' | '@' | '%' | '=')
+}
+
+/// Skip the parameters and final byte of a CSI sequence.
+fn skip_csi<I: Iterator<Item = char>>(chars: &mut I) {
+    for c in chars {
+        if ('@'..='~').contains(&c) {
+            break;
+        }
+    }
+}
+
+/// Skip a control string up to and including its terminator.
+fn skip_string<I: Iterator<Item = char>>(chars: &mut std::iter::Peekable<I>) {
+    while let Some(c) = chars.next() {
+        match c {
+            '\x07' | '\u{9c}' => break,
+            ESC if chars.peek() == Some(&'\\') => {
+                chars.next();
+                break;
+            },
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_escapes() {
+        let text = "echo a\x1b[201~; rm\x1b]0;title\x07 -rf\x1bP1$r\x1b\\ ~\u{9b}31m\x08\n";
+        assert_eq!(sanitize(text), "echo a; rm -rf ~\n");
+        assert_eq!(sanitize("tab\tand\r\nlines"), "tab\tand\r\nlines");
+    }
+
+    #[test]
+    fn paste_confirmation() {
+        assert!(!requires_confirmation("single line\twith tab"));
+        assert!(requires_confirmation("first\nsecond"));
+        assert!(requires_confirmation("command\r"));
+        assert!(requires_confirmation("\x1b[201~"));
+    }
+
+    #[test]
+    fn escape_paths() {
+        assert_eq!(escape_path("/tmp/file.txt", PathQuoting::Single), "/tmp/file.txt");
+        assert_eq!(escape_path("/tmp/it's here", PathQuoting::Single), "'/tmp/it'\\''s here'");
+        assert_eq!(escape_path("/tmp/a $b", PathQuoting::Double), "\"/tmp/a \\$b\"");
+        assert_eq!(escape_path("/tmp/a (1)", PathQuoting::Backslash), "/tmp/a\\ \\(1\\)");
+        assert_eq!(escape_path("/tmp/a\nb", PathQuoting::Backslash), "'/tmp/a\nb'");
+        assert_eq!(escape_path("/tmp/a!b", PathQuoting::Double), "\"/tmp/a\"'!'\"b\"");
+        assert_eq!(escape_path("-rf", PathQuoting::Single), "./-rf");
+        assert_eq!(escape_path("-a b", PathQuoting::Single), "'./-a b'");
+    }
+}
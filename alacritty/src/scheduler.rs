@@ -14,7 +14,9 @@ type Event = GlutinEvent<'static, AlacrittyEvent>;
 pub enum TimerId {
     SelectionScrolling,
     DelayedSearch,
+    SearchCount,
     BlinkCursor,
+    KeySequence,
 }
 
 /// Event scheduled to be emitted at a specific time.
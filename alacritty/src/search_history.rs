@@ -0,0 +1,223 @@
+//! Search history shared between all Alacritty instances.
+
+use std::collections::VecDeque;
+#[cfg(not(windows))]
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+#[cfg(not(windows))]
+use std::os::unix::fs::OpenOptionsExt;
+#[cfg(not(windows))]
+use std::os::unix::io::AsRawFd;
+#[cfg(windows)]
+use std::os::windows::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+#[cfg(windows)]
+use std::thread;
+#[cfg(windows)]
+use std::time::Duration;
+
+/// Name of the file storing the search history.
+const HISTORY_FILE_NAME: &str = "search_history";
+
+/// Load the persistent search history, with the most recent search first.
+///
+/// This never waits for other instances, instead an error of the kind
+/// [`io::ErrorKind::WouldBlock`] is returned while the history is being updated.
+pub fn load(max_entries: usize) -> io::Result<VecDeque<String>> {
+    let path = match history_path() {
+        Some(path) if path.exists() => path,
+        _ => return Ok(VecDeque::new()),
+    };
+
+    let mut file = open_locked(&path, false)?;
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+
+    let mut history = parse(&content);
+    history.truncate(max_entries);
+    Ok(history)
+}
+
+/// Add a new search to the persistent history.
+///
+/// Since this waits for other instances to finish their updates, it should not be called from
+/// the UI thread.
+pub fn push(search: &str, max_entries: usize) -> io::Result<()> {
+    let path = history_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no state directory"))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Hold the lock until the file has been rewritten, to prevent losing concurrent updates.
+    let mut file = open_locked(&path, true)?;
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+
+    let mut history = parse(&content);
+    add_entry(&mut history, search, max_entries);
+
+    // Store oldest search first, making the file easy to read.
+    let mut content = String::new();
+    for search in history.iter().rev() {
+        content.push_str(search);
+        content.push('\n');
+    }
+
+    file.seek(SeekFrom::Start(0))?;
+    file.set_len(0)?;
+    file.write_all(content.as_bytes())
+}
+
+/// Merge the history loaded from the file into the in-memory history.
+///
+/// Recent searches which are missing from the file are kept in front, since they might still be
+/// waiting to be written.
+pub fn merge(history: &mut VecDeque<String>, loaded: VecDeque<String>, max_entries: usize) {
+    let mut merged: VecDeque<String> = history
+        .drain(..)
+        .filter(|search| !search.is_empty())
+        .take_while(|search| !loaded.contains(search))
+        .collect();
+    merged.extend(loaded);
+    merged.truncate(max_entries);
+    *history = merged;
+}
+
+/// Parse the history file's content, with the most recent search first.
+fn parse(content: &str) -> VecDeque<String> {
+    let mut history = VecDeque::new();
+    for search in content.lines().filter(|search| !search.is_empty()) {
+        add_entry(&mut history, search, usize::MAX);
+    }
+    history
+}
+
+/// Add a search to the front of the history, removing duplicates and the oldest entries.
+fn add_entry(history: &mut VecDeque<String>, search: &str, max_entries: usize) {
+    history.retain(|entry| entry != search);
+    history.push_front(search.to_owned());
+    history.truncate(max_entries);
+}
+
+/// Location of the search history file.
+#[cfg(not(windows))]
+fn history_path() -> Option<PathBuf> {
+    let state_home = env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(".local/state")))?;
+
+    Some(state_home.join("alacritty").join(HISTORY_FILE_NAME))
+}
+
+/// Location of the search history file.
+#[cfg(windows)]
+fn history_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|path| path.join("alacritty").join(HISTORY_FILE_NAME))
+}
+
+/// Open the history file with exclusive access.
+///
+/// Without `blocking`, this fails if another instance has the file opened already. The lock is
+/// released once the file is closed. New files are only accessible by the current user.
+#[cfg(not(windows))]
+fn open_locked(path: &Path, blocking: bool) -> io::Result<File> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .mode(0o600)
+        .open(path)?;
+
+    let operation = if blocking { libc::LOCK_EX } else { libc::LOCK_EX | libc::LOCK_NB };
+    if unsafe { libc::flock(file.as_raw_fd(), operation) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(file)
+}
+
+/// Open the history file with exclusive access.
+///
+/// Since the file is opened without any sharing permissions, other instances will fail to open it
+/// until it is closed again. Without `blocking`, this fails instead of retrying.
+#[cfg(windows)]
+fn open_locked(path: &Path, blocking: bool) -> io::Result<File> {
+    /// Windows error code for files which are opened by another process.
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+
+    let mut options = OpenOptions::new();
+    options.read(true).write(true).create(true).truncate(false).share_mode(0);
+
+    let mut retries = if blocking { 50 } else { 0 };
+    loop {
+        match options.open(path) {
+            Err(err) if err.raw_os_error() == Some(ERROR_SHARING_VIOLATION) => {
+                if retries == 0 {
+                    return Err(io::Error::new(io::ErrorKind::WouldBlock, err));
+                }
+
+                thread::sleep(Duration::from_millis(10));
+                retries -= 1;
+            },
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_history() {
+        let history = parse("first\n\nsecond\nfirst\nthird\n");
+        assert_eq!(history, vec!["third", "first", "second"]);
+    }
+
+    #[test]
+    fn add_history_entry() {
+        let mut history = parse("first\nsecond\nthird\n");
+
+        add_entry(&mut history, "second", 3);
+        assert_eq!(history, vec!["second", "third", "first"]);
+
+        add_entry(&mut history, "fourth", 3);
+        assert_eq!(history, vec!["fourth", "second", "third"]);
+    }
+
+    #[test]
+    fn merge_history() {
+        let mut history = parse("old\nshared\nlocal\n");
+        history.push_front(String::new());
+
+        // Only searches newer than those found in the file are kept.
+        merge(&mut history, parse("other\nshared\nnew\n"), 3);
+        assert_eq!(history, vec!["local", "new", "shared"]);
+    }
+
+    #[test]
+    fn locked_history_does_not_block() {
+        let path = std::env::temp_dir().join(format!("alacritty-history-{}", std::process::id()));
+
+        let file = open_locked(&path, true).unwrap();
+        let err = open_locked(&path, false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+        #[cfg(not(windows))]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let metadata = file.metadata().unwrap();
+            assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        }
+
+        drop(file);
+        assert!(open_locked(&path, false).is_ok());
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+//! X11 selection owner offering the same content in multiple formats.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+use x11_clipboard::error::Error;
+use x11_clipboard::xcb::{self, Atom, Window};
+use x11_clipboard::{Context, INCR_CHUNK_SIZE};
+
+use alacritty_terminal::term::ClipboardType;
+
+/// Data offered for every target of a selection.
+type Offers = Arc<RwLock<HashMap<Atom, Vec<(Atom, Arc<Vec<u8>>)>>>>;
+
+/// Owner of the X11 clipboard and primary selection.
+pub struct SelectionOwner {
+    context: Arc<Context>,
+    offers: Offers,
+}
+
+impl SelectionOwner {
+    pub fn new() -> Result<Self, Error> {
+        let context = Arc::new(Context::new(None)?);
+        let offers = Offers::default();
+
+        let thread_context = context.clone();
+        let thread_offers = offers.clone();
+        thread::spawn(move || run(&thread_context, &thread_offers));
+
+        Ok(Self { context, offers })
+    }
+
+    /// Take ownership of a selection, offering `text` as `mime_type` and `plain` as plain text.
+    pub fn store(
+        &self,
+        ty: ClipboardType,
+        mime_type: &str,
+        text: &str,
+        plain: &str,
+    ) -> Result<(), Error> {
+        let selection = match ty {
+            ClipboardType::Selection => self.context.atoms.primary,
+            ClipboardType::Clipboard => self.context.atoms.clipboard,
+        };
+
+        let plain = Arc::new(plain.as_bytes().to_vec());
+        let targets = vec![
+            (self.context.get_atom(mime_type)?, Arc::new(text.as_bytes().to_vec())),
+            (self.context.atoms.utf8_string, plain.clone()),
+            (self.context.get_atom("text/plain;charset=utf-8")?, plain.clone()),
+            (self.context.get_atom("text/plain")?, plain),
+        ];
+
+        self.offers.write().map_err(|_| Error::Lock)?.insert(selection, targets);
+
+        let connection = &self.context.connection;
+        xcb::set_selection_owner(connection, self.context.window, selection, xcb::CURRENT_TIME);
+        connection.flush();
+
+        let owner = xcb::get_selection_owner(connection, selection).get_reply();
+        if owner.map(|reply| reply.owner()).ok() == Some(self.context.window) {
+            Ok(())
+        } else {
+            Err(Error::Owner)
+        }
+    }
+}
+
+/// Transfer of a large value in chunks, using the INCR protocol.
+struct IncrTransfer {
+    target: Atom,
+    value: Arc<Vec<u8>>,
+    pos: usize,
+}
+
+/// Answer selection requests until the connection is closed.
+fn run(context: &Context, offers: &Offers) {
+    let connection = &context.connection;
+    let max_length = connection.get_maximum_request_length() as usize * 4 - 24;
+    let mut transfers: HashMap<(Window, Atom), IncrTransfer> = HashMap::new();
+
+    while let Some(event) = connection.wait_for_event() {
+        match event.response_type() & !0x80 {
+            xcb::SELECTION_REQUEST => {
+                let event = unsafe { xcb::cast_event::<xcb::SelectionRequestEvent>(&event) };
+                let requestor = event.requestor();
+                let mut property = event.property();
+
+                let offers = match offers.read() {
+                    Ok(offers) => offers,
+                    Err(_) => continue,
+                };
+                let targets = offers.get(&event.selection()).map(Vec::as_slice).unwrap_or(&[]);
+                let offer = targets.iter().find(|(target, _)| *target == event.target());
+
+                if event.target() == context.atoms.targets && !targets.is_empty() {
+                    let mut atoms = vec![context.atoms.targets];
+                    atoms.extend(targets.iter().map(|(target, _)| *target));
+                    xcb::change_property(
+                        connection,
+                        xcb::PROP_MODE_REPLACE as u8,
+                        requestor,
+                        property,
+                        xcb::ATOM_ATOM,
+                        32,
+                        &atoms,
+                    );
+                } else if let Some((target, value)) = offer {
+                    if value.len() < max_length {
+                        xcb::change_property(
+                            connection,
+                            xcb::PROP_MODE_REPLACE as u8,
+                            requestor,
+                            property,
+                            *target,
+                            8,
+                            value,
+                        );
+                    } else {
+                        // Send large values in chunks once the requestor deleted the property.
+                        xcb::change_window_attributes(
+                            connection,
+                            requestor,
+                            &[(xcb::CW_EVENT_MASK, xcb::EVENT_MASK_PROPERTY_CHANGE)],
+                        );
+                        xcb::change_property(
+                            connection,
+                            xcb::PROP_MODE_REPLACE as u8,
+                            requestor,
+                            property,
+                            context.atoms.incr,
+                            32,
+                            &[value.len() as u32],
+                        );
+
+                        let transfer =
+                            IncrTransfer { target: *target, value: value.clone(), pos: 0 };
+                        transfers.insert((requestor, property), transfer);
+                    }
+                } else {
+                    // Refuse requests for unknown targets.
+                    property = xcb::NONE;
+                }
+
+                xcb::send_event(
+                    connection,
+                    false,
+                    requestor,
+                    0,
+                    &xcb::SelectionNotifyEvent::new(
+                        event.time(),
+                        requestor,
+                        event.selection(),
+                        event.target(),
+                        property,
+                    ),
+                );
+                connection.flush();
+            },
+            xcb::PROPERTY_NOTIFY => {
+                let event = unsafe { xcb::cast_event::<xcb::PropertyNotifyEvent>(&event) };
+                if event.state() != xcb::PROPERTY_DELETE as u8 {
+                    continue;
+                }
+
+                let key = (event.window(), event.atom());
+                let transfer = match transfers.get_mut(&key) {
+                    Some(transfer) => transfer,
+                    None => continue,
+                };
+
+                // An empty chunk marks the end of the transfer.
+                let len = cmp::min(INCR_CHUNK_SIZE, transfer.value.len() - transfer.pos);
+                xcb::change_property(
+                    connection,
+                    xcb::PROP_MODE_REPLACE as u8,
+                    event.window(),
+                    event.atom(),
+                    transfer.target,
+                    8,
+                    &transfer.value[transfer.pos..transfer.pos + len],
+                );
+                transfer.pos += len;
+
+                if len == 0 {
+                    transfers.remove(&key);
+                }
+                connection.flush();
+            },
+            xcb::SELECTION_CLEAR => {
+                let event = unsafe { xcb::cast_event::<xcb::SelectionClearEvent>(&event) };
+                if let Ok(mut offers) = offers.write() {
+                    offers.remove(&event.selection());
+                }
+            },
+            _ => (),
+        }
+    }
+}
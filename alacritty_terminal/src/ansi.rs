@@ -456,6 +456,9 @@ pub trait Handler {
 
     /// Report text area size in characters.
     fn text_area_size_chars(&mut self) {}
+
+    /// Set xterm's modifyOtherKeys level for the keyboard encoding.
+    fn set_modify_other_keys(&mut self, _: ModifyOtherKeys) {}
 }
 
 /// Terminal cursor configuration.
@@ -520,6 +523,8 @@ pub enum Mode {
     Origin = 6,
     /// ?7
     LineWrap = 7,
+    /// ?9
+    ReportMouseX10 = 9,
     /// ?12
     BlinkingCursor = 12,
     /// 20
@@ -543,6 +548,10 @@ pub enum Mode {
     SgrMouse = 1006,
     /// ?1007
     AlternateScroll = 1007,
+    /// ?1015
+    UrxvtMouse = 1015,
+    /// ?1016
+    SgrPixelsMouse = 1016,
     /// ?1042
     UrgencyHints = 1042,
     /// ?1049
@@ -566,6 +575,7 @@ impl Mode {
                 3 => Mode::ColumnMode,
                 6 => Mode::Origin,
                 7 => Mode::LineWrap,
+                9 => Mode::ReportMouseX10,
                 12 => Mode::BlinkingCursor,
                 25 => Mode::ShowCursor,
                 1000 => Mode::ReportMouseClicks,
@@ -575,6 +585,8 @@ impl Mode {
                 1005 => Mode::Utf8Mouse,
                 1006 => Mode::SgrMouse,
                 1007 => Mode::AlternateScroll,
+                1015 => Mode::UrxvtMouse,
+                1016 => Mode::SgrPixelsMouse,
                 1042 => Mode::UrgencyHints,
                 1049 => Mode::SwapScreenAndSetRestoreCursor,
                 2004 => Mode::BracketedPaste,
@@ -630,6 +642,17 @@ pub enum TabulationClearMode {
     All,
 }
 
+/// Level of xterm's modifyOtherKeys keyboard encoding.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ModifyOtherKeys {
+    /// Disable the encoding of modified keys.
+    Reset,
+    /// Encode modified keys which have no well-defined sequence.
+    EnableExceptWellDefined,
+    /// Encode all modified keys, including control characters.
+    EnableAll,
+}
+
 /// Standard colors.
 ///
 /// The order here matters since the enum should be castable to a `usize` for
@@ -1195,6 +1218,26 @@ where
                 }
             },
             ('M', []) => handler.delete_lines(next_param_or(1) as usize),
+            ('m', [b'>']) => {
+                // XTMODKEYS (CSI > Pp ; Pv m) -- Set key modifier options.
+                let mut params = params_iter.map(|param| param[0]);
+                if params.next() != Some(4) {
+                    unhandled!();
+                    return;
+                }
+
+                let mode = match params.next() {
+                    None | Some(0) => ModifyOtherKeys::Reset,
+                    Some(1) => ModifyOtherKeys::EnableExceptWellDefined,
+                    Some(2) => ModifyOtherKeys::EnableAll,
+                    _ => {
+                        unhandled!();
+                        return;
+                    },
+                };
+
+                handler.set_modify_other_keys(mode);
+            },
             ('m', []) => {
                 if params.is_empty() {
                     handler.terminal_attribute(Attr::Reset);
@@ -1208,6 +1251,10 @@ where
                 }
             },
             ('n', []) => handler.device_status(next_param_or(0) as usize),
+            ('n', [b'>']) if next_param_or(0) == 4 => {
+                // Disable key modifier options (CSI > Ps n).
+                handler.set_modify_other_keys(ModifyOtherKeys::Reset);
+            },
             ('P', []) => handler.delete_chars(next_param_or(1) as usize),
             ('q', [b' ']) => {
                 // DECSCUSR (CSI Ps SP q) -- Set Cursor Style.
@@ -1492,6 +1539,7 @@ mod tests {
         charset: StandardCharset,
         attr: Option<Attr>,
         identity_reported: bool,
+        modify_other_keys: Option<ModifyOtherKeys>,
     }
 
     impl Handler for MockHandler {
@@ -1515,6 +1563,10 @@ mod tests {
         fn reset_state(&mut self) {
             *self = Self::default();
         }
+
+        fn set_modify_other_keys(&mut self, mode: ModifyOtherKeys) {
+            self.modify_other_keys = Some(mode);
+        }
     }
 
     impl Default for MockHandler {
@@ -1524,6 +1576,7 @@ mod tests {
                 charset: StandardCharset::Ascii,
                 attr: None,
                 identity_reported: false,
+                modify_other_keys: None,
             }
         }
     }
@@ -1542,6 +1595,28 @@ mod tests {
         assert_eq!(handler.attr, Some(Attr::Bold));
     }
 
+    #[test]
+    fn parse_modify_other_keys() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        for byte in b"\x1b[>4;2m" {
+            parser.advance(&mut handler, *byte);
+        }
+        assert_eq!(handler.modify_other_keys, Some(ModifyOtherKeys::EnableAll));
+        assert_eq!(handler.attr, None);
+
+        for byte in b"\x1b[>4;1m" {
+            parser.advance(&mut handler, *byte);
+        }
+        assert_eq!(handler.modify_other_keys, Some(ModifyOtherKeys::EnableExceptWellDefined));
+
+        for byte in b"\x1b[>4n" {
+            parser.advance(&mut handler, *byte);
+        }
+        assert_eq!(handler.modify_other_keys, Some(ModifyOtherKeys::Reset));
+    }
+
     #[test]
     fn parse_terminal_identity_csi() {
         let bytes: &[u8] = &[0x1b, b'[', b'1', b'c'];
@@ -50,6 +50,12 @@ pub struct Config<T> {
 pub struct Selection {
     pub semantic_escape_chars: String,
     pub save_to_clipboard: bool,
+
+    /// Regexes for semantic selection, ordered by priority.
+    pub rules: Vec<String>,
+
+    /// Post-processing of copied text.
+    pub copy: SelectionCopy,
 }
 
 impl Default for Selection {
@@ -57,10 +63,24 @@ impl Default for Selection {
         Self {
             semantic_escape_chars: String::from(",│`|:\"' ()[]{}<>\t"),
             save_to_clipboard: Default::default(),
+            rules: Default::default(),
+            copy: Default::default(),
         }
     }
 }
 
+#[derive(ConfigDeserialize, Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub struct SelectionCopy {
+    /// Remove whitespace at the end of every line.
+    pub trim_trailing_whitespace: bool,
+
+    /// Join lines which were wrapped by the terminal in block selections.
+    pub join_wrapped_lines: bool,
+
+    /// Replace consecutive blank lines with a single one.
+    pub collapse_blank_lines: bool,
+}
+
 #[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq)]
 pub struct Cursor {
     pub style: ConfigCursorStyle,
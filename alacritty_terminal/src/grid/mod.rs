@@ -133,6 +133,17 @@ pub struct Grid<T> {
 
     /// Maximum number of lines in history.
     max_scroll_limit: usize,
+
+    /// Number of lines which have been moved into the scrollback history.
+    ///
+    /// This wraps around on overflow, so it is only useful for computing the number of lines
+    /// scrolled since an earlier point in time.
+    #[serde(skip)]
+    scrolled_lines: usize,
+
+    /// Number of times existing lines in the scrollback history were removed or modified.
+    #[serde(skip)]
+    history_generation: usize,
 }
 
 impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
@@ -143,6 +154,8 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
             display_offset: 0,
             saved_cursor: Cursor::default(),
             cursor: Cursor::default(),
+            scrolled_lines: 0,
+            history_generation: 0,
             lines,
             columns,
         }
@@ -153,6 +166,7 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
         let current_history_size = self.history_size();
         if current_history_size > history_size {
             self.raw.shrink_lines(current_history_size - history_size);
+            self.history_generation = self.history_generation.wrapping_add(1);
         }
         self.display_offset = min(self.display_offset, history_size);
         self.max_scroll_limit = history_size;
@@ -268,6 +282,7 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
 
         // Create scrollback for the new lines.
         self.increase_scroll_limit(positions);
+        self.scrolled_lines = self.scrolled_lines.wrapping_add(positions);
 
         // Swap the lines fixed at the top to their target positions after rotation.
         //
@@ -376,6 +391,7 @@ impl<T> Grid<T> {
     pub fn clear_history(&mut self) {
         // Explicitly purge all lines from history.
         self.raw.shrink_lines(self.history_size());
+        self.history_generation = self.history_generation.wrapping_add(1);
     }
 
     /// This is used only for initializing after loading ref-tests.
@@ -423,6 +439,21 @@ impl<T> Grid<T> {
         self.display_offset
     }
 
+    /// Number of lines moved into the scrollback history, wrapping around on overflow.
+    #[inline]
+    pub fn scrolled_lines(&self) -> usize {
+        self.scrolled_lines
+    }
+
+    /// Counter which changes whenever existing lines in the scrollback history are modified.
+    ///
+    /// As long as this is unchanged, lines in the history are only ever shifted upwards by new
+    /// lines scrolling into it, or removed at the top once the history is full.
+    #[inline]
+    pub fn history_generation(&self) -> usize {
+        self.history_generation
+    }
+
     #[inline]
     pub fn cursor_cell(&mut self) -> &mut T {
         let point = self.cursor.point;
@@ -19,6 +19,9 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
         // Use empty template cell for resetting cells due to resize.
         let template = mem::take(&mut self.cursor.template);
 
+        // Lines can be pulled from history or reflowed.
+        self.history_generation = self.history_generation.wrapping_add(1);
+
         match self.lines.cmp(&lines) {
             Ordering::Less => self.grow_lines(lines),
             Ordering::Greater => self.shrink_lines(lines),
@@ -118,6 +118,21 @@ fn scroll_down_with_history() {
     assert_eq!(grid[Line(9)].occ, 1);
 }
 
+#[test]
+fn track_history_changes() {
+    let mut grid = Grid::<usize>::new(3, 1, 5);
+    let generation = grid.history_generation();
+
+    grid.scroll_up::<usize>(&(Line(0)..Line(3)), 2);
+    grid.scroll_up::<usize>(&(Line(0)..Line(3)), 1);
+    assert_eq!(grid.scrolled_lines(), 3);
+    assert_eq!(grid.history_generation(), generation);
+
+    grid.clear_history();
+    assert_eq!(grid.scrolled_lines(), 3);
+    assert_ne!(grid.history_generation(), generation);
+}
+
 // Test that GridIterator works.
 #[test]
 fn test_iter() {
@@ -5,7 +5,8 @@
 //! when text is added/removed/scrolled on the screen. The selection should
 //! also be cleared if the user clicks off of the selection.
 
-use std::cmp::min;
+use std::cell::RefCell;
+use std::cmp::{max, min};
 use std::mem;
 use std::ops::{Bound, Range, RangeBounds};
 
@@ -13,8 +14,12 @@ use crate::ansi::CursorShape;
 use crate::grid::{Dimensions, GridCell, Indexed};
 use crate::index::{Boundary, Column, Line, Point, Side};
 use crate::term::cell::{Cell, Flags};
+use crate::term::search::Match;
 use crate::term::Term;
 
+/// Maximum number of lines searched for selection rule matches around a point.
+const MAX_RULE_SEARCH_LINES: usize = 100;
+
 /// A Point and side within that point.
 #[derive(Debug, Copy, Clone, PartialEq)]
 struct Anchor {
@@ -28,6 +33,30 @@ impl Anchor {
     }
 }
 
+/// Selection rule matches around the endpoints of a semantic selection.
+#[derive(Debug, Clone, PartialEq)]
+struct RuleMatches {
+    start: Point,
+    end: Point,
+    start_match: Option<Match>,
+    end_match: Option<Match>,
+}
+
+impl RuleMatches {
+    /// Move all points by a number of lines.
+    fn shift(&mut self, delta: i32) {
+        self.start.line -= delta;
+        self.end.line -= delta;
+
+        for rule_match in self.start_match.iter_mut().chain(self.end_match.iter_mut()) {
+            let (mut start, mut end) = (*rule_match.start(), *rule_match.end());
+            start.line -= delta;
+            end.line -= delta;
+            *rule_match = start..=end;
+        }
+    }
+}
+
 /// Represents a range of selected cells.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct SelectionRange {
@@ -118,21 +147,34 @@ pub enum SelectionType {
 pub struct Selection {
     pub ty: SelectionType,
     region: Range<Anchor>,
+
+    /// Selection rule matches resolved for the current semantic selection endpoints.
+    rule_matches: RefCell<Option<RuleMatches>>,
 }
 
 impl Selection {
     pub fn new(ty: SelectionType, location: Point, side: Side) -> Selection {
         Self {
             region: Range { start: Anchor::new(location, side), end: Anchor::new(location, side) },
+            rule_matches: RefCell::new(None),
             ty,
         }
     }
 
     /// Update the end of the selection.
     pub fn update(&mut self, point: Point, side: Side) {
+        if self.region.end.point != point {
+            self.rule_matches.replace(None);
+        }
+
         self.region.end = Anchor::new(point, side);
     }
 
+    /// Resolve selection rule matches again the next time the selection is converted to a range.
+    pub fn clear_rule_matches(&self) {
+        self.rule_matches.replace(None);
+    }
+
     pub fn rotate<D: Dimensions>(
         mut self,
         dimensions: &D,
@@ -143,6 +185,11 @@ impl Selection {
         let range_bottom = range.end;
         let range_top = range.start;
 
+        // Keep the resolved rule matches in sync with the rotated content.
+        if let Some(rule_matches) = self.rule_matches.get_mut() {
+            rule_matches.shift(delta);
+        }
+
         let (mut start, mut end) = (&mut self.region.start, &mut self.region.end);
         if start.point > end.point {
             mem::swap(&mut start, &mut end);
@@ -288,13 +335,21 @@ impl Selection {
         match self.ty {
             SelectionType::Simple => self.range_simple(start, end, columns),
             SelectionType::Block => self.range_block(start, end),
-            SelectionType::Semantic => Some(Self::range_semantic(term, start.point, end.point)),
+            SelectionType::Semantic => Some(self.range_semantic(term, start.point, end.point)),
             SelectionType::Lines => Some(Self::range_lines(term, start.point, end.point)),
         }
     }
 
-    fn range_semantic<T>(term: &Term<T>, mut start: Point, mut end: Point) -> SelectionRange {
-        if start == end {
+    fn range_semantic<T>(
+        &self,
+        term: &Term<T>,
+        mut start: Point,
+        mut end: Point,
+    ) -> SelectionRange {
+        // Expand to the longest selection rule match around the endpoints.
+        let (start_match, end_match) = self.rule_matches(term, start, end);
+
+        if start == end && start_match.is_none() {
             if let Some(matching) = term.bracket_search(start) {
                 if (matching.line == start.line && matching.column < start.column)
                     || (matching.line > start.line)
@@ -308,12 +363,97 @@ impl Selection {
             }
         }
 
-        let start = term.semantic_search_left(start);
-        let end = term.semantic_search_right(end);
+        let start = match start_match {
+            Some(rule_match) => *rule_match.start(),
+            None => term.semantic_search_left(start),
+        };
+        let end = match end_match {
+            Some(rule_match) => *rule_match.end(),
+            None => term.semantic_search_right(end),
+        };
 
         SelectionRange { start, end, is_block: false }
     }
 
+    /// Selection rule matches around the start and end of the selection.
+    ///
+    /// The matches are only searched for once after the selection was created or updated, since
+    /// running every rule on each redraw would be too expensive.
+    fn rule_matches<T>(
+        &self,
+        term: &Term<T>,
+        start: Point,
+        end: Point,
+    ) -> (Option<Match>, Option<Match>) {
+        let mut rule_matches = self.rule_matches.borrow_mut();
+        match &*rule_matches {
+            Some(rm) if rm.start == start && rm.end == end => {
+                return (rm.start_match.clone(), rm.end_match.clone());
+            },
+            _ => (),
+        }
+
+        let start_match = Self::rule_match(term, start);
+        let end_match =
+            if start == end { start_match.clone() } else { Self::rule_match(term, end) };
+
+        *rule_matches = Some(RuleMatches {
+            start,
+            end,
+            start_match: start_match.clone(),
+            end_match: end_match.clone(),
+        });
+
+        (start_match, end_match)
+    }
+
+    /// Find the longest selection rule match containing a point.
+    ///
+    /// If multiple rules have a match of the same length, the first rule takes precedence.
+    fn rule_match<T>(term: &Term<T>, point: Point) -> Option<Match> {
+        let rules = term.selection_rules();
+        if rules.is_empty() {
+            return None;
+        }
+
+        // Limit search to the wrapped line around the point.
+        let mut start = term.line_search_left(point);
+        start.line = max(start.line, point.line - MAX_RULE_SEARCH_LINES);
+        let mut end = term.line_search_right(point);
+        end.line = min(end.line, point.line + MAX_RULE_SEARCH_LINES);
+
+        let columns = term.columns() as i32;
+        let len = |rm: &Match| {
+            (rm.end().line - rm.start().line).0 * columns + rm.end().column.0 as i32
+                - rm.start().column.0 as i32
+        };
+
+        let mut longest: Option<Match> = None;
+        for regex in rules.iter() {
+            // Try every match start before the point, since matches might overlap.
+            let mut match_start = start;
+            while match_start <= point {
+                let cell = &term.grid()[match_start];
+                let rule_match = if cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                    None
+                } else {
+                    term.regex_match_at(regex, match_start, end)
+                };
+
+                match (rule_match, &longest) {
+                    (Some(rule_match), _) if *rule_match.end() < point => (),
+                    (Some(rule_match), Some(current)) if len(&rule_match) <= len(current) => (),
+                    (Some(rule_match), _) => longest = Some(rule_match),
+                    (None, _) => (),
+                }
+
+                match_start = match_start.add(term, Boundary::None, 1);
+            }
+        }
+
+        longest
+    }
+
     fn range_lines<T>(term: &Term<T>, start: Point, end: Point) -> SelectionRange {
         let start = term.line_search_left(start);
         let end = term.line_search_right(end);
@@ -542,6 +682,77 @@ mod tests {
         });
     }
 
+    #[test]
+    fn semantic_selection_rules() {
+        let mut config = MockConfig::default();
+        config.selection.rules = vec![String::from(r"\d+(\.\d+){3}(:\d+)?"), String::from(r"\d+")];
+        let size = SizeInfo::new(20., 1., 1.0, 1.0, 0.0, 0.0, false);
+        let mut term = Term::new(&config, size, ());
+        for (i, c) in "at 127.0.0.1:80, 42".chars().enumerate() {
+            term.grid_mut()[Line(0)][Column(i)].c = c;
+        }
+
+        // Longest rule match containing the point.
+        let location = Point::new(Line(0), Column(5));
+        let selection = Selection::new(SelectionType::Semantic, location, Side::Left);
+        assert_eq!(selection.to_range(&term).unwrap(), SelectionRange {
+            start: Point::new(Line(0), Column(3)),
+            end: Point::new(Line(0), Column(14)),
+            is_block: false,
+        });
+
+        // Lower priority rule.
+        let location = Point::new(Line(0), Column(18));
+        let selection = Selection::new(SelectionType::Semantic, location, Side::Left);
+        assert_eq!(selection.to_range(&term).unwrap(), SelectionRange {
+            start: Point::new(Line(0), Column(17)),
+            end: Point::new(Line(0), Column(18)),
+            is_block: false,
+        });
+
+        // Fallback to semantic escape chars.
+        let location = Point::new(Line(0), Column(1));
+        let selection = Selection::new(SelectionType::Semantic, location, Side::Left);
+        assert_eq!(selection.to_range(&term).unwrap(), SelectionRange {
+            start: Point::new(Line(0), Column(0)),
+            end: Point::new(Line(0), Column(1)),
+            is_block: false,
+        });
+    }
+
+    #[test]
+    fn semantic_selection_overlapping_rules() {
+        let mut config = MockConfig::default();
+        config.selection.rules = vec![String::from("abc|bcd+")];
+        let size = SizeInfo::new(8., 1., 1.0, 1.0, 0.0, 0.0, false);
+        let mut term = Term::new(&config, size, ());
+        for (i, c) in "xabcdddd".chars().enumerate() {
+            term.grid_mut()[Line(0)][Column(i)].c = c;
+        }
+
+        // Match overlapping with an earlier match.
+        let location = Point::new(Line(0), Column(6));
+        let mut selection = Selection::new(SelectionType::Semantic, location, Side::Left);
+        let range = SelectionRange {
+            start: Point::new(Line(0), Column(2)),
+            end: Point::new(Line(0), Column(7)),
+            is_block: false,
+        };
+        assert_eq!(selection.to_range(&term).unwrap(), range);
+
+        // Matches are not searched again until the selection is updated.
+        term.grid_mut()[Line(0)][Column(3)].c = ' ';
+        assert_eq!(selection.to_range(&term).unwrap(), range);
+
+        selection.update(Point::new(Line(0), Column(5)), Side::Left);
+        selection.update(location, Side::Left);
+        assert_eq!(selection.to_range(&term).unwrap(), SelectionRange {
+            start: Point::new(Line(0), Column(4)),
+            end: Point::new(Line(0), Column(7)),
+            is_block: false,
+        });
+    }
+
     #[test]
     fn simple_selection() {
         let size = (10, 5);
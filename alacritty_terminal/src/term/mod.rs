@@ -1,25 +1,28 @@
 //! Exports the `Term` type which is a high-level API for the Grid.
 
+use std::cell::{Ref, RefCell};
 use std::cmp::{max, min};
 use std::ops::{Index, IndexMut, Range};
 use std::sync::Arc;
 use std::{mem, ptr, str};
 
 use bitflags::bitflags;
-use log::{debug, trace};
+use log::{debug, error, trace};
 use serde::{Deserialize, Serialize};
 use unicode_width::UnicodeWidthChar;
 
 use crate::ansi::{
-    self, Attr, CharsetIndex, Color, CursorShape, CursorStyle, Handler, NamedColor, StandardCharset,
+    self, Attr, CharsetIndex, Color, CursorShape, CursorStyle, Handler, ModifyOtherKeys,
+    NamedColor, StandardCharset,
 };
-use crate::config::Config;
+use crate::config::{Config, SelectionCopy};
 use crate::event::{Event, EventListener};
 use crate::grid::{Dimensions, Grid, GridIterator, Scroll};
 use crate::index::{self, Boundary, Column, Direction, Line, Point, Side};
 use crate::selection::{Selection, SelectionRange};
 use crate::term::cell::{Cell, Flags, LineLength};
 use crate::term::color::{Colors, Rgb};
+use crate::term::search::{RegexSearch, DEFAULT_DFA_SIZE_LIMIT};
 use crate::vi_mode::{ViModeCursor, ViMotion};
 
 pub mod cell;
@@ -40,6 +43,21 @@ const TITLE_STACK_MAX_DEPTH: usize = 4096;
 /// Default tab interval, corresponding to terminfo `it` value.
 const INITIAL_TABSTOPS: usize = 8;
 
+/// Receiver for text extracted from the grid.
+pub trait TextSink {
+    /// Append a character, alongside the cell it originates from.
+    ///
+    /// Line breaks which are not part of any cell are passed without a cell.
+    fn push_cell(&mut self, c: char, cell: Option<&Cell>);
+}
+
+impl TextSink for String {
+    #[inline]
+    fn push_cell(&mut self, c: char, _cell: Option<&Cell>) {
+        self.push(c);
+    }
+}
+
 bitflags! {
     pub struct TermMode: u32 {
         const NONE                = 0;
@@ -57,11 +75,17 @@ bitflags! {
         const FOCUS_IN_OUT        = 0b0000_0000_1000_0000_0000;
         const ALT_SCREEN          = 0b0000_0001_0000_0000_0000;
         const MOUSE_DRAG          = 0b0000_0010_0000_0000_0000;
-        const MOUSE_MODE          = 0b0000_0010_0000_0100_1000;
+        const MOUSE_MODE          = 0b0001_0000_0010_0000_0100_1000;
         const UTF8_MOUSE          = 0b0000_0100_0000_0000_0000;
         const ALTERNATE_SCROLL    = 0b0000_1000_0000_0000_0000;
         const VI                  = 0b0001_0000_0000_0000_0000;
         const URGENCY_HINTS       = 0b0010_0000_0000_0000_0000;
+        const MODIFY_OTHER_KEYS   = 0b0100_0000_0000_0000_0000;
+        const MODIFY_ALL_KEYS     = 0b1000_0000_0000_0000_0000;
+        const MOUSE_X10           = 0b0001_0000_0000_0000_0000_0000;
+        const URXVT_MOUSE         = 0b0010_0000_0000_0000_0000_0000;
+        const SGR_PIXELS_MOUSE    = 0b0100_0000_0000_0000_0000_0000;
+        const MOUSE_ENCODING      = 0b0110_0000_0100_0000_0010_0000;
         const ANY                 = std::u32::MAX;
     }
 }
@@ -244,6 +268,12 @@ pub struct Term<T> {
 
     semantic_escape_chars: String,
 
+    /// Regexes for semantic selection, ordered by priority.
+    selection_rules: SelectionRules,
+
+    /// Post-processing of copied text.
+    selection_copy: SelectionCopy,
+
     /// Modified terminal colors.
     colors: Colors,
 
@@ -310,6 +340,8 @@ impl<T> Term<T> {
             scroll_region,
             colors: color::Colors::default(),
             semantic_escape_chars: config.selection.semantic_escape_chars.to_owned(),
+            selection_rules: SelectionRules::new(&config.selection.rules),
+            selection_copy: config.selection.copy,
             cursor_style: None,
             default_cursor_style: config.cursor.style(),
             vi_mode_cursor_style: config.cursor.vi_mode_style(),
@@ -328,6 +360,14 @@ impl<T> Term<T> {
         T: EventListener,
     {
         self.semantic_escape_chars = config.selection.semantic_escape_chars.to_owned();
+        // Keep the compiled regexes if the rules did not change.
+        if self.selection_rules.patterns != config.selection.rules {
+            self.selection_rules = SelectionRules::new(&config.selection.rules);
+            if let Some(selection) = &self.selection {
+                selection.clear_rule_matches();
+            }
+        }
+        self.selection_copy = config.selection.copy;
         self.default_cursor_style = config.cursor.style();
         self.vi_mode_cursor_style = config.cursor.vi_mode_style();
 
@@ -347,51 +387,93 @@ impl<T> Term<T> {
 
     /// Convert the active selection to a String.
     pub fn selection_to_string(&self) -> Option<String> {
+        let mut res = String::new();
+        self.write_selection(&mut res)?;
+        Some(self.process_copied_text(res))
+    }
+
+    /// Apply the configured post-processing to copied text.
+    pub fn process_copied_text(&self, text: String) -> String {
+        let SelectionCopy { trim_trailing_whitespace, collapse_blank_lines, .. } =
+            self.selection_copy;
+        if !trim_trailing_whitespace && !collapse_blank_lines {
+            return text;
+        }
+
+        let mut res = String::with_capacity(text.len());
+        let mut previous_blank = false;
+        let mut lines = text.split('\n').peekable();
+        while let Some(line) = lines.next() {
+            let last = lines.peek().is_none();
+            let blank = line.trim().is_empty();
+
+            if collapse_blank_lines && blank && previous_blank && !last {
+                continue;
+            }
+            previous_blank = blank;
+
+            res.push_str(if trim_trailing_whitespace { line.trim_end() } else { line });
+
+            if !last {
+                res.push('\n');
+            }
+        }
+
+        res
+    }
+
+    /// Write the selected text into a sink, alongside the cells it originates from.
+    ///
+    /// Returns `None` if there is no selection.
+    pub fn write_selection<S: TextSink>(&self, sink: &mut S) -> Option<()> {
         let selection_range = self.selection.as_ref().and_then(|s| s.to_range(self))?;
         let SelectionRange { start, end, is_block } = selection_range;
 
-        let mut res = String::new();
-
         if is_block {
             for line in (start.line.0..end.line.0).map(Line::from) {
-                res += &self.line_to_string(line, start.column..end.column, start.column.0 != 0);
+                self.write_line(sink, line, start.column..end.column, start.column.0 != 0);
 
                 // If the last column is included, newline is appended automatically.
-                if end.column != self.columns() - 1 {
-                    res += "\n";
+                let wrapped = self.grid[line][self.last_column()].flags.contains(Flags::WRAPLINE);
+                if end.column != self.columns() - 1
+                    && !(wrapped && self.selection_copy.join_wrapped_lines)
+                {
+                    sink.push_cell('\n', None);
                 }
             }
-            res += &self.line_to_string(end.line, start.column..end.column, true);
+            self.write_line(sink, end.line, start.column..end.column, true);
         } else {
-            res = self.bounds_to_string(start, end);
+            self.write_bounds(sink, start, end);
         }
 
-        Some(res)
+        Some(())
     }
 
     /// Convert range between two points to a String.
     pub fn bounds_to_string(&self, start: Point, end: Point) -> String {
         let mut res = String::new();
+        self.write_bounds(&mut res, start, end);
+        res
+    }
 
+    /// Write the text between two points into a sink.
+    pub fn write_bounds<S: TextSink>(&self, sink: &mut S, start: Point, end: Point) {
         for line in (start.line.0..=end.line.0).map(Line::from) {
             let start_col = if line == start.line { start.column } else { Column(0) };
             let end_col = if line == end.line { end.column } else { self.last_column() };
 
-            res += &self.line_to_string(line, start_col..end_col, line == end.line);
+            self.write_line(sink, line, start_col..end_col, line == end.line);
         }
-
-        res
     }
 
-    /// Convert a single line in the grid to a String.
-    fn line_to_string(
+    /// Write a single line in the grid into a sink.
+    fn write_line<S: TextSink>(
         &self,
+        sink: &mut S,
         line: Line,
         mut cols: Range<Column>,
         include_wrapped_wide: bool,
-    ) -> String {
-        let mut text = String::new();
-
+    ) {
         let grid_line = &self.grid[line];
         let line_length = min(grid_line.line_length(), cols.end + 1);
 
@@ -419,11 +501,11 @@ impl<T> Term<T> {
 
             if !cell.flags.intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER) {
                 // Push cells primary character.
-                text.push(cell.c);
+                sink.push_cell(cell.c, Some(cell));
 
                 // Push zero-width characters.
                 for c in cell.zerowidth().into_iter().flatten() {
-                    text.push(*c);
+                    sink.push_cell(*c, Some(cell));
                 }
             }
         }
@@ -432,7 +514,7 @@ impl<T> Term<T> {
             && (line_length.0 == 0
                 || !self.grid[line][line_length - 1].flags.contains(Flags::WRAPLINE))
         {
-            text.push('\n');
+            sink.push_cell('\n', None);
         }
 
         // If wide char is not part of the selection, but leading spacer is, include it.
@@ -441,10 +523,9 @@ impl<T> Term<T> {
             && grid_line[line_length - 1].flags.contains(Flags::LEADING_WIDE_CHAR_SPACER)
             && include_wrapped_wide
         {
-            text.push(self.grid[line - 1i32][Column(0)].c);
+            let cell = &self.grid[line - 1i32][Column(0)];
+            sink.push_cell(cell.c, Some(cell));
         }
-
-        text
     }
 
     /// Terminal content required for rendering.
@@ -464,6 +545,12 @@ impl<T> Term<T> {
         &self.grid
     }
 
+    /// Colors changed at runtime through escape sequences.
+    #[inline]
+    pub fn colors(&self) -> &Colors {
+        &self.colors
+    }
+
     /// Mutable access for swapping out the grid during tests.
     #[cfg(test)]
     pub fn grid_mut(&mut self) -> &mut Grid<Cell> {
@@ -723,6 +810,14 @@ impl<T> Term<T> {
         &self.semantic_escape_chars
     }
 
+    /// Regexes for semantic selection, ordered by priority.
+    ///
+    /// The regexes are compiled when they are used for the first time.
+    #[inline]
+    pub fn selection_rules(&self) -> Ref<'_, [RegexSearch]> {
+        self.selection_rules.compiled()
+    }
+
     /// Active terminal cursor style.
     ///
     /// While vi mode is active, this will automatically return the vi mode cursor style.
@@ -1525,6 +1620,11 @@ impl<T: EventListener> Handler for Term<T> {
             ansi::Mode::ShowCursor => self.mode.insert(TermMode::SHOW_CURSOR),
             ansi::Mode::CursorKeys => self.mode.insert(TermMode::APP_CURSOR),
             // Mouse protocols are mutually exclusive.
+            ansi::Mode::ReportMouseX10 => {
+                self.mode.remove(TermMode::MOUSE_MODE);
+                self.mode.insert(TermMode::MOUSE_X10);
+                self.event_proxy.send_event(Event::MouseCursorDirty);
+            },
             ansi::Mode::ReportMouseClicks => {
                 self.mode.remove(TermMode::MOUSE_MODE);
                 self.mode.insert(TermMode::MOUSE_REPORT_CLICK);
@@ -1544,13 +1644,21 @@ impl<T: EventListener> Handler for Term<T> {
             ansi::Mode::BracketedPaste => self.mode.insert(TermMode::BRACKETED_PASTE),
             // Mouse encodings are mutually exclusive.
             ansi::Mode::SgrMouse => {
-                self.mode.remove(TermMode::UTF8_MOUSE);
+                self.mode.remove(TermMode::MOUSE_ENCODING);
                 self.mode.insert(TermMode::SGR_MOUSE);
             },
             ansi::Mode::Utf8Mouse => {
-                self.mode.remove(TermMode::SGR_MOUSE);
+                self.mode.remove(TermMode::MOUSE_ENCODING);
                 self.mode.insert(TermMode::UTF8_MOUSE);
             },
+            ansi::Mode::UrxvtMouse => {
+                self.mode.remove(TermMode::MOUSE_ENCODING);
+                self.mode.insert(TermMode::URXVT_MOUSE);
+            },
+            ansi::Mode::SgrPixelsMouse => {
+                self.mode.remove(TermMode::MOUSE_ENCODING);
+                self.mode.insert(TermMode::SGR_PIXELS_MOUSE);
+            },
             ansi::Mode::AlternateScroll => self.mode.insert(TermMode::ALTERNATE_SCROLL),
             ansi::Mode::LineWrap => self.mode.insert(TermMode::LINE_WRAP),
             ansi::Mode::LineFeedNewLine => self.mode.insert(TermMode::LINE_FEED_NEW_LINE),
@@ -1577,6 +1685,10 @@ impl<T: EventListener> Handler for Term<T> {
             },
             ansi::Mode::ShowCursor => self.mode.remove(TermMode::SHOW_CURSOR),
             ansi::Mode::CursorKeys => self.mode.remove(TermMode::APP_CURSOR),
+            ansi::Mode::ReportMouseX10 => {
+                self.mode.remove(TermMode::MOUSE_X10);
+                self.event_proxy.send_event(Event::MouseCursorDirty);
+            },
             ansi::Mode::ReportMouseClicks => {
                 self.mode.remove(TermMode::MOUSE_REPORT_CLICK);
                 self.event_proxy.send_event(Event::MouseCursorDirty);
@@ -1593,6 +1705,8 @@ impl<T: EventListener> Handler for Term<T> {
             ansi::Mode::BracketedPaste => self.mode.remove(TermMode::BRACKETED_PASTE),
             ansi::Mode::SgrMouse => self.mode.remove(TermMode::SGR_MOUSE),
             ansi::Mode::Utf8Mouse => self.mode.remove(TermMode::UTF8_MOUSE),
+            ansi::Mode::UrxvtMouse => self.mode.remove(TermMode::URXVT_MOUSE),
+            ansi::Mode::SgrPixelsMouse => self.mode.remove(TermMode::SGR_PIXELS_MOUSE),
             ansi::Mode::AlternateScroll => self.mode.remove(TermMode::ALTERNATE_SCROLL),
             ansi::Mode::LineWrap => self.mode.remove(TermMode::LINE_WRAP),
             ansi::Mode::LineFeedNewLine => self.mode.remove(TermMode::LINE_FEED_NEW_LINE),
@@ -1726,6 +1840,53 @@ impl<T: EventListener> Handler for Term<T> {
         let text = format!("\x1b[8;{};{}t", self.screen_lines(), self.columns());
         self.event_proxy.send_event(Event::PtyWrite(text));
     }
+
+    #[inline]
+    fn set_modify_other_keys(&mut self, mode: ModifyOtherKeys) {
+        trace!("Setting modifyOtherKeys to {:?}", mode);
+        self.mode.remove(TermMode::MODIFY_OTHER_KEYS | TermMode::MODIFY_ALL_KEYS);
+        match mode {
+            ModifyOtherKeys::Reset => (),
+            ModifyOtherKeys::EnableExceptWellDefined => {
+                self.mode.insert(TermMode::MODIFY_OTHER_KEYS)
+            },
+            ModifyOtherKeys::EnableAll => self.mode.insert(TermMode::MODIFY_ALL_KEYS),
+        }
+    }
+}
+
+/// Regexes used for semantic selection, which are compiled on demand.
+#[derive(Debug, Default)]
+struct SelectionRules {
+    /// Uncompiled regexes, ordered by priority.
+    patterns: Vec<String>,
+
+    /// Compiled regexes, once they have been used.
+    compiled: RefCell<Option<Vec<RegexSearch>>>,
+}
+
+impl SelectionRules {
+    fn new(patterns: &[String]) -> Self {
+        Self { patterns: patterns.to_vec(), compiled: Default::default() }
+    }
+
+    /// Get the compiled regexes, compiling them if necessary.
+    ///
+    /// Invalid or overly complex regexes are logged and ignored.
+    fn compiled(&self) -> Ref<'_, [RegexSearch]> {
+        if self.compiled.borrow().is_none() {
+            let mut compiled = Vec::new();
+            for rule in &self.patterns {
+                match RegexSearch::with_options(rule, Default::default(), DEFAULT_DFA_SIZE_LIMIT) {
+                    Ok(regex) => compiled.push(regex),
+                    Err(err) => error!("Invalid selection rule {:?}: {}", rule, err),
+                }
+            }
+            *self.compiled.borrow_mut() = Some(compiled);
+        }
+
+        Ref::map(self.compiled.borrow(), |compiled| compiled.as_deref().unwrap_or_default())
+    }
 }
 
 /// Terminal version for escape sequence reports.
@@ -1930,6 +2091,38 @@ mod tests {
     use crate::selection::{Selection, SelectionType};
     use crate::term::cell::{Cell, Flags};
 
+    #[test]
+    fn selection_copy_processing() {
+        let mut config = MockConfig::default();
+        config.selection.copy.trim_trailing_whitespace = true;
+        config.selection.copy.collapse_blank_lines = true;
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let term = Term::new(&config, size, ());
+
+        let text = String::from("a  \n\n \n\nb\t\n\n");
+        assert_eq!(term.process_copied_text(text), "a\n\nb\n\n");
+    }
+
+    #[test]
+    fn selection_rules_compile_lazily() {
+        let mut config = MockConfig::default();
+        config.selection.rules = vec![String::from(r"\d+"), String::from("(")];
+        let size = SizeInfo::new(5., 3., 1.0, 1.0, 0.0, 0.0, false);
+        let mut term = Term::new(&config, size, ());
+        assert!(term.selection_rules.compiled.borrow().is_none());
+
+        // Invalid rules are ignored.
+        assert_eq!(term.selection_rules().len(), 1);
+
+        // Compiled rules are kept until they change.
+        term.update_config(&config);
+        assert!(term.selection_rules.compiled.borrow().is_some());
+
+        config.selection.rules.pop();
+        term.update_config(&config);
+        assert!(term.selection_rules.compiled.borrow().is_none());
+    }
+
     #[test]
     fn semantic_selection_works() {
         let size = SizeInfo::new(5., 3., 1.0, 1.0, 0.0, 0.0, false);
@@ -2210,6 +2403,30 @@ mod tests {
         assert_eq!(term.title, None);
     }
 
+    #[test]
+    fn mouse_modes_are_exclusive() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+
+        term.set_mode(ansi::Mode::ReportMouseClicks);
+        term.set_mode(ansi::Mode::ReportMouseX10);
+        assert!(term.mode().contains(TermMode::MOUSE_X10));
+        assert!(!term.mode().contains(TermMode::MOUSE_REPORT_CLICK));
+        assert!(term.mode().intersects(TermMode::MOUSE_MODE));
+
+        term.set_mode(ansi::Mode::SgrMouse);
+        term.set_mode(ansi::Mode::UrxvtMouse);
+        assert!(term.mode().contains(TermMode::URXVT_MOUSE));
+        assert!(!term.mode().contains(TermMode::SGR_MOUSE));
+
+        term.set_mode(ansi::Mode::SgrPixelsMouse);
+        assert_eq!(*term.mode() & TermMode::MOUSE_ENCODING, TermMode::SGR_PIXELS_MOUSE);
+
+        term.unset_mode(ansi::Mode::SgrPixelsMouse);
+        term.unset_mode(ansi::Mode::ReportMouseX10);
+        assert!(!term.mode().intersects(TermMode::MOUSE_ENCODING | TermMode::MOUSE_MODE));
+    }
+
     #[test]
     fn parse_cargo_version() {
         assert!(version_number(env!("CARGO_PKG_VERSION")) >= 10_01);
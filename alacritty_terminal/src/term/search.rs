@@ -1,8 +1,17 @@
-use std::cmp::max;
-use std::mem;
+use std::cell::RefCell;
+use std::cmp::{max, min};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::mem::{self, size_of};
 use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
-use regex_automata::{dense, DenseDFA, Error as RegexError, DFA};
+use bitflags::bitflags;
+use regex_automata::{
+    dense, nfa, DenseDFA, Error as DfaError, ErrorKind as DfaErrorKind, StateID, DFA,
+};
+use regex_syntax::ParserBuilder;
 
 use crate::grid::{BidirectionalIterator, Dimensions, GridIterator, Indexed};
 use crate::index::{Boundary, Column, Direction, Point, Side};
@@ -12,40 +21,260 @@ use crate::term::Term;
 /// Used to match equal brackets, when performing a bracket-pair selection.
 const BRACKET_PAIRS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
 
+/// Default maximum size of a single search DFA in bytes.
+pub const DEFAULT_DFA_SIZE_LIMIT: usize = 10 * 1024 * 1024;
+
+/// Characters which need to be escaped for literal searches.
+const REGEX_META_CHARS: &str = "\\.+*?()|[]{}^$#&-~";
+
 pub type Match = RangeInclusive<Point>;
 
+bitflags! {
+    /// Options controlling how the search input is interpreted.
+    pub struct SearchFlags: u8 {
+        /// Always ignore case.
+        const CASE_INSENSITIVE = 0b0001;
+        /// Ignore case unless the search contains an uppercase character.
+        const SMART_CASE       = 0b0010;
+        /// Match the search input literally, instead of as a regex.
+        const LITERAL          = 0b0100;
+        /// Only accept matches which are not surrounded by word characters.
+        const WHOLE_WORD       = 0b1000;
+    }
+}
+
+impl Default for SearchFlags {
+    fn default() -> Self {
+        SearchFlags::SMART_CASE
+    }
+}
+
+/// Errors occurring while compiling a search regex.
+#[derive(Clone, Debug)]
+pub enum RegexError {
+    /// Invalid or unsupported regex syntax.
+    Invalid(DfaError),
+
+    /// The compiled regex would exceed the size limit.
+    TooComplex,
+
+    /// Compilation was aborted through a [`CancelHandle`].
+    Cancelled,
+}
+
+impl Error for RegexError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RegexError::Invalid(err) => err.source(),
+            RegexError::TooComplex | RegexError::Cancelled => None,
+        }
+    }
+}
+
+impl Display for RegexError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RegexError::Invalid(err) => write!(f, "{}", err),
+            RegexError::TooComplex => write!(f, "regex too complex"),
+            RegexError::Cancelled => write!(f, "regex compilation cancelled"),
+        }
+    }
+}
+
+impl From<DfaError> for RegexError {
+    fn from(val: DfaError) -> Self {
+        match val.kind() {
+            // Running out of state IDs means the DFA has too many states.
+            DfaErrorKind::StateIDOverflow { .. } => RegexError::TooComplex,
+            _ => RegexError::Invalid(val),
+        }
+    }
+}
+
+/// Handle for aborting the compilation of a regex from another thread.
+#[derive(Clone, Debug, Default)]
+pub struct CancelHandle(Arc<AtomicBool>);
+
+impl CancelHandle {
+    /// Abort the compilation as soon as possible.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Check if the compilation was aborted.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+thread_local! {
+    /// Maximum number of states and cancellation of the DFA built on this thread.
+    static BUILD_LIMITS: RefCell<Option<(usize, CancelHandle)>> = Default::default();
+}
+
+/// DFA state ID which enforces the limits of the DFA while it is being built.
+///
+/// Determinization checks the maximum state ID whenever a new state is added, so the number of
+/// states and cancellation are checked there, instead of after the full DFA has been built.
+///
+/// This relies on regex-automata 0.1 calling `StateID::max_id` for every state added by the
+/// dense DFA builder, which is verified by the `state_limit_checked_while_building` test. Newer
+/// versions of regex-automata offer size limits directly and should use those instead.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LimitedStateID(u32);
+
+// Safety: `max_id` never exceeds the maximum value representable by the inner `u32`.
+unsafe impl StateID for LimitedStateID {
+    #[inline]
+    fn from_usize(n: usize) -> Self {
+        Self(n as u32)
+    }
+
+    #[inline]
+    fn to_usize(self) -> usize {
+        self.0 as usize
+    }
+
+    fn max_id() -> usize {
+        let max_id = BUILD_LIMITS.with(|limits| match &*limits.borrow() {
+            Some((_, cancel)) if cancel.is_cancelled() => 0,
+            Some((max_states, _)) => max_states.saturating_sub(1),
+            None => usize::MAX,
+        });
+        min(max_id, u32::MAX as usize)
+    }
+
+    #[inline]
+    fn read_bytes(slice: &[u8]) -> Self {
+        Self(<u32 as StateID>::read_bytes(slice))
+    }
+
+    #[inline]
+    fn write_bytes(self, slice: &mut [u8]) {
+        self.0.write_bytes(slice)
+    }
+}
+
+/// DFA with a limited number of states.
+type SizedDFA = DenseDFA<Vec<LimitedStateID>, LimitedStateID>;
+
 /// Terminal regex search state.
 #[derive(Clone, Debug)]
 pub struct RegexSearch {
     /// Locate end of match searching right.
-    right_fdfa: DenseDFA<Vec<usize>, usize>,
+    right_fdfa: SizedDFA,
     /// Locate start of match searching right.
-    right_rdfa: DenseDFA<Vec<usize>, usize>,
+    right_rdfa: SizedDFA,
 
     /// Locate start of match searching left.
-    left_fdfa: DenseDFA<Vec<usize>, usize>,
+    left_fdfa: SizedDFA,
     /// Locate end of match searching left.
-    left_rdfa: DenseDFA<Vec<usize>, usize>,
+    left_rdfa: SizedDFA,
+
+    /// Reject matches which are part of a bigger word.
+    whole_word: bool,
 }
 
 impl RegexSearch {
     /// Build the forward and backward search DFAs.
     pub fn new(search: &str) -> Result<RegexSearch, RegexError> {
-        // Check case info for smart case
-        let has_uppercase = search.chars().any(|c| c.is_uppercase());
+        Self::with_flags(search, SearchFlags::default())
+    }
+
+    /// Build the forward and backward search DFAs using the specified search flags.
+    pub fn with_flags(search: &str, flags: SearchFlags) -> Result<RegexSearch, RegexError> {
+        Self::with_options(search, flags, DEFAULT_DFA_SIZE_LIMIT)
+    }
+
+    /// Build the forward and backward search DFAs with a maximum size for each DFA.
+    ///
+    /// If any of the DFAs would use more than `size_limit` bytes, [`RegexError::TooComplex`] is
+    /// returned instead.
+    pub fn with_options(
+        search: &str,
+        flags: SearchFlags,
+        size_limit: usize,
+    ) -> Result<RegexSearch, RegexError> {
+        Self::cancellable(search, flags, size_limit, &CancelHandle::default())
+    }
+
+    /// Build the search DFAs with a size limit, aborting once `cancel` is triggered.
+    ///
+    /// Construction stops as soon as a DFA exceeds the size limit, so pathological regexes never
+    /// use significantly more time or memory than the limit allows.
+    pub fn cancellable(
+        search: &str,
+        flags: SearchFlags,
+        size_limit: usize,
+        cancel: &CancelHandle,
+    ) -> Result<RegexSearch, RegexError> {
+        // Escape all regex syntax for literal searches.
+        let literal;
+        let search = if flags.contains(SearchFlags::LITERAL) {
+            literal = escape(search);
+            literal.as_str()
+        } else {
+            search
+        };
+
+        // Check case info for smart case.
+        let case_insensitive = flags.contains(SearchFlags::CASE_INSENSITIVE)
+            || (flags.contains(SearchFlags::SMART_CASE) && !search.chars().any(char::is_uppercase));
+
+        // Limit the number of states based on the size of each state's transition table.
+        //
+        // Syntax errors are reported by the DFA builder below.
+        let hir = ParserBuilder::new().case_insensitive(case_insensitive).build().parse(search);
+        let alphabet_len = match hir {
+            Ok(hir) => nfa::Builder::new().build(&hir)?.byte_classes().alphabet_len(),
+            Err(_) => 256,
+        };
+        let max_states = size_limit / (alphabet_len * size_of::<LimitedStateID>());
 
         // Create Regex DFAs for all search directions.
+        //
+        // Premultiplication is disabled, since state IDs are used to count the states.
         let mut builder = dense::Builder::new();
-        let builder = builder.case_insensitive(!has_uppercase);
+        let builder = builder.case_insensitive(case_insensitive).premultiply(false);
+
+        let build = |builder: &dense::Builder| -> Result<SizedDFA, RegexError> {
+            BUILD_LIMITS.with(|limits| *limits.borrow_mut() = Some((max_states, cancel.clone())));
+            let dfa = builder.build_with_size::<LimitedStateID>(search);
+            BUILD_LIMITS.with(|limits| *limits.borrow_mut() = None);
+
+            if cancel.is_cancelled() {
+                return Err(RegexError::Cancelled);
+            }
+            Ok(dfa?)
+        };
 
-        let left_fdfa = builder.clone().reverse(true).build(search)?;
-        let left_rdfa = builder.clone().anchored(true).longest_match(true).build(search)?;
+        let left_fdfa = build(builder.clone().reverse(true))?;
+        let left_rdfa = build(builder.clone().anchored(true).longest_match(true))?;
 
-        let right_fdfa = builder.clone().build(search)?;
-        let right_rdfa = builder.anchored(true).longest_match(true).reverse(true).build(search)?;
+        let right_fdfa = build(builder)?;
+        let right_rdfa = build(builder.anchored(true).longest_match(true).reverse(true))?;
 
-        Ok(RegexSearch { right_fdfa, right_rdfa, left_fdfa, left_rdfa })
+        let whole_word = flags.contains(SearchFlags::WHOLE_WORD);
+
+        Ok(RegexSearch { right_fdfa, right_rdfa, left_fdfa, left_rdfa, whole_word })
+    }
+}
+
+/// Escape all regex meta characters.
+fn escape(search: &str) -> String {
+    let mut escaped = String::with_capacity(search.len());
+    for c in search.chars() {
+        if REGEX_META_CHARS.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
     }
+    escaped
+}
+
+/// Check if a character is considered part of a word for whole-word searches.
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
 }
 
 impl<T> Term<T> {
@@ -181,6 +410,13 @@ impl<T> Term<T> {
         Some(match_start..=match_end)
     }
 
+    /// Find the longest regex match starting at the origin point.
+    pub fn regex_match_at(&self, dfas: &RegexSearch, start: Point, end: Point) -> Option<Match> {
+        let match_end = self.regex_search(start, end, Direction::Right, &dfas.left_rdfa)?;
+
+        Some(start..=match_end)
+    }
+
     /// Find the next regex match.
     ///
     /// This will always return the side of the first match which is farthest from the start point.
@@ -403,6 +639,45 @@ impl<T> Term<T> {
         point
     }
 
+    /// Check if a match is not directly adjacent to any word characters.
+    fn is_whole_word(&self, regex_match: &Match) -> bool {
+        let wide = Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER;
+        let last_column = self.last_column();
+
+        // Check the cell before the match, ignoring spacers and unwrapped linebreaks.
+        let start = *regex_match.start();
+        let mut iter = self.grid.iter_from(start);
+        let before = loop {
+            match iter.prev() {
+                Some(cell) if cell.flags.intersects(wide) => continue,
+                Some(cell) if cell.point.line == start.line => break Some(cell.c),
+                Some(cell) if cell.flags.contains(Flags::WRAPLINE) => break Some(cell.c),
+                _ => break None,
+            }
+        };
+
+        if matches!(before, Some(c) if is_word_char(c)) {
+            return false;
+        }
+
+        // Check the cell after the match, ignoring spacers and unwrapped linebreaks.
+        let end = self.expand_wide(*regex_match.end(), Direction::Right);
+        if end.column == last_column && !self.grid[end].flags.contains(Flags::WRAPLINE) {
+            return true;
+        }
+
+        let mut iter = self.grid.iter_from(end);
+        let after = loop {
+            match iter.next() {
+                Some(cell) if cell.flags.intersects(wide) => continue,
+                Some(cell) => break Some(cell.c),
+                None => break None,
+            }
+        };
+
+        !matches!(after, Some(c) if is_word_char(c))
+    }
+
     /// Find the beginning of the current line across linewraps.
     pub fn line_search_left(&self, mut point: Point) -> Point {
         while point.line > self.topmost_line()
@@ -474,27 +749,43 @@ impl<'a, T> Iterator for RegexIter<'a, T> {
     type Item = Match;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.done {
-            return None;
-        }
+        loop {
+            if self.done {
+                return None;
+            }
 
-        // Since the end itself might be a single cell match, we search one more time.
-        if self.point == self.end {
-            self.done = true;
-        }
+            // Since the end itself might be a single cell match, we search one more time.
+            if self.point == self.end {
+                self.done = true;
+            }
 
-        let regex_match = self.next_match()?;
+            let regex_match = self.next_match()?;
 
-        self.point = *regex_match.end();
-        if self.point == self.end {
-            // Stop when the match terminates right on the end limit.
-            self.done = true;
-        } else {
-            // Move the new search origin past the match.
-            self.skip();
-        }
+            // Skip matches which are part of a bigger word.
+            let rejected = self.dfas.whole_word && !self.term.is_whole_word(&regex_match);
 
-        Some(regex_match)
+            // Overlapping matches could still be whole words, so the search continues right after
+            // the first cell of rejected matches instead of moving past them.
+            //
+            // Searching left only reports matches which end before the origin, so the match end
+            // is its first cell in that direction.
+            self.point = match self.direction {
+                Direction::Right if rejected => *regex_match.start(),
+                _ => *regex_match.end(),
+            };
+
+            if self.point == self.end {
+                // Stop when the match terminates right on the end limit.
+                self.done = true;
+            } else {
+                // Move the new search origin past the match.
+                self.skip();
+            }
+
+            if !rejected {
+                return Some(regex_match);
+            }
+        }
     }
 }
 
@@ -798,4 +1089,128 @@ mod tests {
         let mut iter = RegexIter::new(start, end, Direction::Right, &term, &dfas);
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn case_flags() {
+        let term = mock_term("alacritty Alacritty");
+
+        let start = Point::new(Line(0), Column(0));
+        let end = Point::new(Line(0), Column(18));
+        let lower_match = Some(start..=Point::new(Line(0), Column(8)));
+        let upper_match = Some(Point::new(Line(0), Column(10))..=end);
+
+        // Smart case ignores case only without uppercase characters.
+        let dfas = RegexSearch::with_flags("alacritty", SearchFlags::SMART_CASE).unwrap();
+        assert_eq!(term.regex_search_right(&dfas, start, end), lower_match);
+        let dfas = RegexSearch::with_flags("Alacritty", SearchFlags::SMART_CASE).unwrap();
+        assert_eq!(term.regex_search_right(&dfas, start, end), upper_match);
+
+        // Case insensitive always ignores case.
+        let dfas = RegexSearch::with_flags("ALACRITTY", SearchFlags::CASE_INSENSITIVE).unwrap();
+        assert_eq!(term.regex_search_right(&dfas, start, end), lower_match);
+
+        // Without any flags, case is respected.
+        let dfas = RegexSearch::with_flags("alacritty", SearchFlags::empty()).unwrap();
+        let start = Point::new(Line(0), Column(1));
+        assert_eq!(term.regex_search_right(&dfas, start, end), None);
+    }
+
+    #[test]
+    fn literal_flag() {
+        let term = mock_term("abc a.c (a+)");
+
+        let start = Point::new(Line(0), Column(0));
+        let end = Point::new(Line(0), Column(11));
+
+        let dfas = RegexSearch::with_flags("a.c", SearchFlags::LITERAL).unwrap();
+        let match_start = Point::new(Line(0), Column(4));
+        let match_end = Point::new(Line(0), Column(6));
+        assert_eq!(term.regex_search_right(&dfas, start, end), Some(match_start..=match_end));
+
+        let dfas = RegexSearch::with_flags("(a+)", SearchFlags::LITERAL).unwrap();
+        let match_start = Point::new(Line(0), Column(8));
+        assert_eq!(term.regex_search_right(&dfas, start, end), Some(match_start..=end));
+    }
+
+    #[test]
+    fn whole_word_flag() {
+        #[rustfmt::skip]
+        let term = mock_term("\
+            foobar foo_ foo\r\n\
+            barbarbarbarfoo\n\
+            foo\
+        ");
+
+        let dfas = RegexSearch::with_flags("foo", SearchFlags::WHOLE_WORD).unwrap();
+
+        let start = Point::new(Line(0), Column(0));
+        let end = Point::new(Line(2), Column(2));
+        let whole_word = Point::new(Line(0), Column(12))..=Point::new(Line(0), Column(14));
+
+        let matches: Vec<_> = RegexIter::new(start, end, Direction::Right, &term, &dfas).collect();
+        assert_eq!(matches, vec![whole_word.clone()]);
+
+        let matches: Vec<_> = RegexIter::new(end, start, Direction::Left, &term, &dfas).collect();
+        assert_eq!(matches, vec![whole_word]);
+    }
+
+    #[test]
+    fn whole_word_overlapping_matches() {
+        let term = mock_term("foo-bar baz");
+
+        // The first match is rejected, but overlaps with a whole-word match.
+        let dfas = RegexSearch::with_flags("o-bar|bar baz", SearchFlags::WHOLE_WORD).unwrap();
+
+        let start = Point::new(Line(0), Column(0));
+        let end = Point::new(Line(0), Column(10));
+        let whole_word = Point::new(Line(0), Column(4))..=end;
+
+        let matches: Vec<_> = RegexIter::new(start, end, Direction::Right, &term, &dfas).collect();
+        assert_eq!(matches, vec![whole_word.clone()]);
+
+        let matches: Vec<_> = RegexIter::new(end, start, Direction::Left, &term, &dfas).collect();
+        assert_eq!(matches, vec![whole_word]);
+    }
+
+    #[test]
+    fn size_limit() {
+        let flags = SearchFlags::default();
+
+        // Exceeding the size limit stops the exponential blowup while building the DFA.
+        let result = RegexSearch::with_options("(a|b)*a.{20}", flags, 1024 * 1024);
+        assert!(matches!(result, Err(RegexError::TooComplex)));
+
+        // Exceeding the configured size limit.
+        let result = RegexSearch::with_options("alacritty", flags, 10);
+        assert!(matches!(result, Err(RegexError::TooComplex)));
+
+        let result = RegexSearch::with_options("(", flags, DEFAULT_DFA_SIZE_LIMIT);
+        assert!(matches!(result, Err(RegexError::Invalid(_))));
+    }
+
+    #[test]
+    fn state_limit_checked_while_building() {
+        let build = || dense::Builder::new().build_with_size::<LimitedStateID>("abc");
+
+        // Without limits, the maximum state ID only depends on the inner `u32`.
+        assert_eq!(LimitedStateID::max_id(), u32::MAX as usize);
+        assert!(build().is_ok());
+
+        // The builder must stop once the limit is reached.
+        let limits = Some((2, CancelHandle::default()));
+        BUILD_LIMITS.with(|build_limits| *build_limits.borrow_mut() = limits);
+        let result = build().map_err(RegexError::from);
+        BUILD_LIMITS.with(|build_limits| *build_limits.borrow_mut() = None);
+        assert!(matches!(result, Err(RegexError::TooComplex)));
+    }
+
+    #[test]
+    fn cancel_compilation() {
+        let cancel = CancelHandle::default();
+        cancel.cancel();
+
+        let flags = SearchFlags::default();
+        let result = RegexSearch::cancellable("(a|b)*a.{20}", flags, usize::MAX, &cancel);
+        assert!(matches!(result, Err(RegexError::Cancelled)));
+    }
 }
@@ -52,6 +52,18 @@ pub fn master_fd() -> RawFd {
     FD.load(Ordering::Relaxed) as RawFd
 }
 
+/// Process group ID of the terminal's foreground process.
+///
+/// Falls back to the child process ID if the foreground process group is not available.
+pub fn foreground_pid() -> pid_t {
+    let pid = unsafe { libc::tcgetpgrp(master_fd()) };
+    if pid < 0 {
+        child_pid()
+    } else {
+        pid
+    }
+}
+
 /// Get raw fds for master/slave ends of a new PTY.
 fn make_pty(size: winsize) -> (RawFd, RawFd) {
     let mut win_size = size;